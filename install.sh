@@ -419,9 +419,9 @@ echo -e "   ${CYAN}injecttools${NC}              ${MAGENTA}# Interactive menu${N
 echo -e "   ${CYAN}injecttools --help${NC}       ${MAGENTA}# Show all options${NC}"
 echo ""
 echo -e "${YELLOW}📚 Examples:${NC}"
-echo -e "   ${CYAN}injecttools -t host.com -s cdn.cloudflare.com${NC}"
-echo -e "   ${CYAN}injecttools -t host.com -d cloudflare.com --crtsh${NC}"
-echo -e "   ${CYAN}injecttools --view-results${NC}"
+echo -e "   ${CYAN}injecttools test cdn.cloudflare.com -t host.com${NC}"
+echo -e "   ${CYAN}injecttools discover cloudflare.com -t host.com${NC}"
+echo -e "   ${CYAN}injecttools results show${NC}"
 echo ""
 echo -e "${BLUE}👤 Created by: ${CYAN}t.me/hoshiyomi_id${NC}"
 echo -e "${BLUE}🐛 Report bugs: ${CYAN}github.com/hoshiyomiX/InjectTools/issues${NC}"
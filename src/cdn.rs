@@ -0,0 +1,360 @@
+use crate::outln;
+use crate::config::Config;
+use colored::Colorize;
+use ipnetwork::IpNetwork;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+const BUNDLED_RANGES: &str = include_str!("../ranges/cdn-ranges.txt");
+
+/// Suffix target CNAME per provider
+const CNAME_SUFFIXES: &[(&str, Provider)] = &[
+    ("cloudflare.net", Provider::Cloudflare),
+    ("cloudflare.com", Provider::Cloudflare),
+    ("cloudfront.net", Provider::CloudFront),
+    ("fastly.net", Provider::Fastly),
+    ("fastlylb.net", Provider::Fastly),
+    ("akamaiedge.net", Provider::Akamai),
+    ("akamai.net", Provider::Akamai),
+    ("akamaized.net", Provider::Akamai),
+    ("akamaihd.net", Provider::Akamai),
+    ("edgekey.net", Provider::Akamai),
+    ("edgesuite.net", Provider::Akamai),
+    ("googlehosted.com", Provider::Google),
+    ("googleusercontent.com", Provider::Google),
+    ("l.google.com", Provider::Google),
+    ("b-cdn.net", Provider::Bunny),
+    ("bunnycdn.com", Provider::Bunny),
+];
+
+/// CDN / fronting provider yang dikenali
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Provider {
+    Cloudflare,
+    CloudFront,
+    Fastly,
+    Akamai,
+    Google,
+    Bunny,
+}
+
+impl Provider {
+    pub const ALL: [Provider; 6] = [
+        Provider::Cloudflare,
+        Provider::CloudFront,
+        Provider::Fastly,
+        Provider::Akamai,
+        Provider::Google,
+        Provider::Bunny,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Provider::Cloudflare => "Cloudflare",
+            Provider::CloudFront => "CloudFront",
+            Provider::Fastly => "Fastly",
+            Provider::Akamai => "Akamai",
+            Provider::Google => "Google",
+            Provider::Bunny => "Bunny",
+        }
+    }
+}
+
+impl fmt::Display for Provider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Provider {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Provider::ALL
+            .into_iter()
+            .find(|p| p.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| anyhow::anyhow!("Provider tidak dikenal: {}", s))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct TrieNode {
+    children: [Option<usize>; 2],
+    provider: Option<Provider>,
+}
+
+// Binary prefix trie, lookup = longest prefix match
+#[derive(Debug, Clone)]
+struct PrefixTrie {
+    width: u32,
+    nodes: Vec<TrieNode>,
+}
+
+impl PrefixTrie {
+    fn new(width: u32) -> Self {
+        Self {
+            width,
+            nodes: vec![TrieNode::default()],
+        }
+    }
+
+    fn bit(&self, bits: u128, index: u32) -> usize {
+        ((bits >> (self.width - 1 - index)) & 1) as usize
+    }
+
+    fn insert(&mut self, bits: u128, prefix: u8, provider: Provider) {
+        let mut node = 0;
+        for i in 0..prefix as u32 {
+            let bit = self.bit(bits, i);
+            node = match self.nodes[node].children[bit] {
+                Some(next) => next,
+                None => {
+                    self.nodes.push(TrieNode::default());
+                    let next = self.nodes.len() - 1;
+                    self.nodes[node].children[bit] = Some(next);
+                    next
+                }
+            };
+        }
+        self.nodes[node].provider = Some(provider);
+    }
+
+    fn lookup(&self, bits: u128) -> Option<Provider> {
+        let mut node = 0;
+        let mut best = self.nodes[0].provider;
+        for i in 0..self.width {
+            match self.nodes[node].children[self.bit(bits, i)] {
+                Some(next) => {
+                    node = next;
+                    if let Some(provider) = self.nodes[node].provider {
+                        best = Some(provider);
+                    }
+                }
+                None => break,
+            }
+        }
+        best
+    }
+}
+
+/// Database IP range CDN (IPv4 + IPv6)
+#[derive(Debug, Clone)]
+pub struct RangeDb {
+    v4: PrefixTrie,
+    v6: PrefixTrie,
+    entries: Vec<(Provider, IpNetwork)>,
+}
+
+impl RangeDb {
+    /// Parse format `<provider> <cidr>` per baris, `#` = komentar
+    pub fn parse(content: &str) -> anyhow::Result<Self> {
+        let mut db = Self {
+            v4: PrefixTrie::new(32),
+            v6: PrefixTrie::new(128),
+            entries: Vec::new(),
+        };
+
+        for (idx, line) in content.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut parts = line.split_whitespace();
+            let (provider, cidr) = match (parts.next(), parts.next()) {
+                (Some(provider), Some(cidr)) => (provider, cidr),
+                _ => return Err(anyhow::anyhow!("Baris {} tidak valid: {}", idx + 1, line)),
+            };
+            let provider = Provider::from_str(provider)
+                .map_err(|e| anyhow::anyhow!("Baris {}: {}", idx + 1, e))?;
+            let network = IpNetwork::from_str(cidr)
+                .map_err(|e| anyhow::anyhow!("Baris {}: CIDR {} tidak valid ({})", idx + 1, cidr, e))?;
+
+            db.insert(provider, network);
+        }
+
+        if db.entries.is_empty() {
+            return Err(anyhow::anyhow!("Range database kosong"));
+        }
+
+        Ok(db)
+    }
+
+    pub fn bundled() -> Self {
+        Self::parse(BUNDLED_RANGES).expect("bundled cdn-ranges.txt harus valid")
+    }
+
+    fn insert(&mut self, provider: Provider, network: IpNetwork) {
+        match network {
+            IpNetwork::V4(net) => {
+                self.v4.insert(u32::from(net.network()) as u128, net.prefix(), provider)
+            }
+            IpNetwork::V6(net) => self.v6.insert(u128::from(net.network()), net.prefix(), provider),
+        }
+        self.entries.push((provider, network));
+    }
+
+    pub fn lookup(&self, ip: IpAddr) -> Option<Provider> {
+        match ip {
+            IpAddr::V4(addr) => self.v4.lookup(u32::from(addr) as u128),
+            IpAddr::V6(addr) => match addr.to_ipv4_mapped() {
+                Some(v4) => self.v4.lookup(u32::from(v4) as u128),
+                None => self.v6.lookup(u128::from(addr)),
+            },
+        }
+    }
+
+    /// Semua range milik satu provider
+    pub fn ranges(&self, provider: Provider) -> Vec<IpNetwork> {
+        self.entries
+            .iter()
+            .filter(|(p, _)| *p == provider)
+            .map(|(_, net)| *net)
+            .collect()
+    }
+
+    pub fn count(&self, provider: Provider) -> usize {
+        self.entries.iter().filter(|(p, _)| *p == provider).count()
+    }
+}
+
+/// Database aktif: file hasil update di config dir, fallback ke bundled
+pub fn db() -> &'static RangeDb {
+    static DB: OnceLock<RangeDb> = OnceLock::new();
+    DB.get_or_init(|| {
+        let path = Config::ranges_path();
+        if !path.exists() {
+            return RangeDb::bundled();
+        }
+
+        match fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| RangeDb::parse(&content))
+        {
+            Ok(db) => db,
+            Err(e) => {
+                eprintln!(
+                    "{} {} ({}), pakai range bawaan",
+                    "⚠️  Range database tidak valid:".yellow(),
+                    path.display(),
+                    e
+                );
+                RangeDb::bundled()
+            }
+        }
+    })
+}
+
+/// Provider CDN untuk IP (string hasil DNS)
+pub fn lookup(ip: &str) -> Option<Provider> {
+    IpAddr::from_str(ip).ok().and_then(|addr| db().lookup(addr))
+}
+
+/// Provider dari nama CNAME (cocok di batas label, mis. x.cdn.cloudflare.net)
+pub fn lookup_cname(name: &str) -> Option<Provider> {
+    let name = name.trim_end_matches('.').to_lowercase();
+    CNAME_SUFFIXES.iter().find_map(|(suffix, provider)| {
+        let matches = name == *suffix
+            || name.strip_suffix(suffix).is_some_and(|rest| rest.ends_with('.'));
+        matches.then_some(*provider)
+    })
+}
+
+/// Provider dari rantai CNAME: yang paling ujung (paling dekat ke IP) menang
+pub fn classify_cnames(chain: &[String]) -> Option<Provider> {
+    chain.iter().rev().find_map(|name| lookup_cname(name))
+}
+
+pub fn provider_name(provider: Option<Provider>) -> &'static str {
+    provider.map(Provider::name).unwrap_or("Non-CDN")
+}
+
+/// Update range database dari file lokal atau URL (http/https)
+pub async fn update_ranges(source: &str) -> anyhow::Result<()> {
+    let content = if source.starts_with("http://") || source.starts_with("https://") {
+        outln!("\n{} {}", "📡 Downloading range database:".cyan(), source);
+        let response = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()?
+            .get(source)
+            .header("User-Agent", concat!("InjectTools/", env!("CARGO_PKG_VERSION")))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!("Download gagal: HTTP {}", response.status()));
+        }
+        response.text().await?
+    } else {
+        fs::read_to_string(source)?
+    };
+
+    // Validasi dulu sebelum menimpa database lama
+    let db = RangeDb::parse(&content)?;
+
+    let path = Config::ranges_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, content)?;
+
+    outln!("\n{}", "✓ Range database updated".green());
+    outln!("{} {}", "File:".bright_black(), path.display().to_string().bright_black());
+    for provider in Provider::ALL {
+        outln!("  {:<12} {} ranges", provider.name(), db.count(provider).to_string().cyan());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    fn ip(s: &str) -> IpAddr {
+        IpAddr::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn prefix_trie_prefers_longest_match() {
+        let mut trie = PrefixTrie::new(32);
+        trie.insert(u32::from(Ipv4Addr::new(10, 0, 0, 0)) as u128, 8, Provider::Akamai);
+        trie.insert(u32::from(Ipv4Addr::new(10, 1, 0, 0)) as u128, 16, Provider::Fastly);
+
+        assert_eq!(trie.lookup(u32::from(Ipv4Addr::new(10, 1, 2, 3)) as u128), Some(Provider::Fastly));
+        assert_eq!(trie.lookup(u32::from(Ipv4Addr::new(10, 2, 0, 1)) as u128), Some(Provider::Akamai));
+        assert_eq!(trie.lookup(u32::from(Ipv4Addr::new(11, 0, 0, 1)) as u128), None);
+    }
+
+    #[test]
+    fn range_db_lookup_v4_v6_and_mapped() {
+        let db = RangeDb::parse(
+            "# komentar\n\
+             akamai 23.0.0.0/12\n\
+             cloudflare 23.1.0.0/16 # lebih spesifik\n\
+             cloudflare 2606:4700::/32\n",
+        )
+        .unwrap();
+
+        assert_eq!(db.lookup(ip("23.1.5.5")), Some(Provider::Cloudflare));
+        assert_eq!(db.lookup(ip("23.2.5.5")), Some(Provider::Akamai));
+        assert_eq!(db.lookup(ip("8.8.8.8")), None);
+        assert_eq!(db.lookup(ip("2606:4700::6810:84e5")), Some(Provider::Cloudflare));
+        assert_eq!(db.lookup(ip("2001:db8::1")), None);
+        assert_eq!(db.lookup(ip("::ffff:23.1.0.1")), Some(Provider::Cloudflare));
+        assert_eq!(db.count(Provider::Cloudflare), 2);
+    }
+
+    #[test]
+    fn range_db_rejects_invalid_lines() {
+        assert!(RangeDb::parse("cloudflare\n").is_err());
+        assert!(RangeDb::parse("nope 1.1.1.0/24\n").is_err());
+        assert!(RangeDb::parse("cloudflare 1.1.1.0/33\n").is_err());
+        assert!(RangeDb::parse("# kosong\n").is_err());
+        assert_eq!(RangeDb::bundled().lookup(ip("104.16.0.1")), Some(Provider::Cloudflare));
+    }
+}
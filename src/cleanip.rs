@@ -0,0 +1,364 @@
+use colored::Colorize;
+use ipnetwork::IpNetwork;
+use rand::Rng;
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::outln;
+use crate::cdn::{self, Provider};
+use crate::dns::IpFamily;
+use crate::probe;
+use crate::scanner::{self, IpResult, Job, ScanOptions, ScanResult};
+use crate::ui;
+
+// Batas alamat per CIDR untuk mode sweep (setara /16 IPv4)
+const MAX_SWEEP_PER_CIDR: u128 = 65_536;
+/// Batas --sample per CIDR; range IPv6 besar tidak pernah di-expand penuh
+pub const MAX_SAMPLE_PER_CIDR: usize = 65_536;
+
+/// Pengaturan clean-IP scan
+#[derive(Debug, Clone)]
+pub struct CleanIpOptions {
+    pub cidrs: Vec<IpNetwork>,
+    /// Jumlah IP acak per CIDR, 1..=MAX_SAMPLE_PER_CIDR (diabaikan kalau sweep).
+    /// CIDR yang lebih kecil dari sample dites semua alamatnya
+    pub sample: usize,
+    /// Test semua alamat di tiap CIDR
+    pub sweep: bool,
+    /// Jumlah handshake per IP untuk menghitung success rate
+    pub attempts: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct CleanIpResult {
+    pub ip: IpAddr,
+    pub provider: Option<Provider>,
+    pub attempts: u32,
+    pub successes: u32,
+    pub avg_latency_ms: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+impl CleanIpResult {
+    pub fn success_rate(&self) -> f64 {
+        if self.attempts == 0 {
+            return 0.0;
+        }
+        self.successes as f64 / self.attempts as f64
+    }
+}
+
+/// Range Cloudflare dari range database (IPv4, atau IPv6 kalau --ip-version 6)
+pub fn default_ranges(family: IpFamily) -> Vec<IpNetwork> {
+    cdn::db()
+        .ranges(Provider::Cloudflare)
+        .into_iter()
+        .filter(|net| match family {
+            IpFamily::V6 => net.is_ipv6(),
+            _ => net.is_ipv4(),
+        })
+        .collect()
+}
+
+/// Parse daftar CIDR dipisah koma/spasi, IP tunggal dianggap /32 atau /128
+pub fn parse_cidrs(input: &str) -> anyhow::Result<Vec<IpNetwork>> {
+    input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            IpNetwork::from_str(s).map_err(|e| anyhow::anyhow!("CIDR {} tidak valid ({})", s, e))
+        })
+        .collect()
+}
+
+fn network_size(net: &IpNetwork) -> u128 {
+    let host_bits = match net {
+        IpNetwork::V4(n) => 32 - n.prefix() as u32,
+        IpNetwork::V6(n) => 128 - n.prefix() as u32,
+    };
+    if host_bits >= 128 {
+        u128::MAX
+    } else {
+        1u128 << host_bits
+    }
+}
+
+fn nth_address(net: &IpNetwork, offset: u128) -> IpAddr {
+    match net {
+        IpNetwork::V4(n) => IpAddr::V4(Ipv4Addr::from(u32::from(n.network()).wrapping_add(offset as u32))),
+        IpNetwork::V6(n) => IpAddr::V6(Ipv6Addr::from(u128::from(n.network()).wrapping_add(offset))),
+    }
+}
+
+/// Daftar IP yang akan dites (sweep atau sampling acak per CIDR)
+pub fn expand_targets(options: &CleanIpOptions) -> anyhow::Result<Vec<IpAddr>> {
+    if !options.sweep && !(1..=MAX_SAMPLE_PER_CIDR).contains(&options.sample) {
+        return Err(anyhow::anyhow!(
+            "--sample harus antara 1 dan {} per CIDR (dapat {})",
+            MAX_SAMPLE_PER_CIDR,
+            options.sample
+        ));
+    }
+
+    let mut rng = rand::thread_rng();
+    let mut targets = Vec::new();
+
+    for net in &options.cidrs {
+        let size = network_size(net);
+
+        if options.sweep {
+            if size > MAX_SWEEP_PER_CIDR {
+                return Err(anyhow::anyhow!(
+                    "CIDR {} terlalu besar untuk sweep ({} alamat), pakai --sample",
+                    net,
+                    size
+                ));
+            }
+            targets.extend((0..size).map(|offset| nth_address(net, offset)));
+        } else if size <= options.sample as u128 {
+            targets.extend((0..size).map(|offset| nth_address(net, offset)));
+        } else {
+            let mut picked = HashSet::new();
+            while picked.len() < options.sample {
+                picked.insert(rng.gen_range(0..size));
+            }
+            let mut picked: Vec<u128> = picked.into_iter().collect();
+            picked.sort_unstable();
+            targets.extend(picked.into_iter().map(|offset| nth_address(net, offset)));
+        }
+    }
+
+    Ok(targets)
+}
+
+// Beberapa kali TLS handshake ke satu IP dengan SNI target,
+// berhenti di antara attempt kalau scan dibatalkan
+async fn probe_address(
+    ip: IpAddr,
+    target: &str,
+    options: &ScanOptions,
+    max_attempts: u32,
+    running: &AtomicBool,
+) -> CleanIpResult {
+    let timeout = Duration::from_secs(options.timeout);
+    let mut attempts = 0;
+    let mut successes = 0;
+    let mut total_latency = 0;
+    let mut last_error = None;
+
+    while attempts < max_attempts && (attempts == 0 || running.load(Ordering::SeqCst)) {
+        attempts += 1;
+        let result = probe::tls_probe(ip, options.port(), options.sni(target), timeout).await;
+        match result.latency_ms {
+            Some(latency) if result.outcome.is_ok() => {
+                successes += 1;
+                total_latency += latency;
+            }
+            _ => last_error = Some(result.outcome.to_string()),
+        }
+    }
+
+    CleanIpResult {
+        ip,
+        provider: cdn::db().lookup(ip),
+        attempts,
+        successes,
+        avg_latency_ms: (successes > 0).then(|| total_latency / successes as u64),
+        last_error,
+    }
+}
+
+pub async fn scan(
+    target: &str,
+    clean: &CleanIpOptions,
+    options: &ScanOptions,
+    running: Arc<AtomicBool>,
+) -> anyhow::Result<Vec<CleanIpResult>> {
+    let addresses = expand_targets(clean)?;
+    let total = addresses.len();
+
+    outln!("\n{}", "Starting clean IP scan...".cyan());
+    outln!(
+        "{} {} CIDR | {} IP | {} attempt/IP",
+        "Total:".bright_black(),
+        clean.cidrs.len().to_string().yellow(),
+        total.to_string().yellow(),
+        clean.attempts.to_string().yellow()
+    );
+    outln!("{} {}\n", "SNI:".bright_black(), options.sni(target).cyan());
+
+    let mut results = scanner::worker_pool(
+        addresses,
+        options,
+        &running,
+        |ip| {
+            let running = &running;
+            Job::Run(format!("Testing: {}", ip), async move {
+                probe_address(ip, target, options, clean.attempts.max(1), running).await
+            })
+        },
+        |_| {},
+    )
+    .await;
+
+    // Ranking: success rate tertinggi, lalu latency terendah
+    results.sort_by(|a, b| {
+        b.success_rate()
+            .total_cmp(&a.success_rate())
+            .then(a.avg_latency_ms.unwrap_or(u64::MAX).cmp(&b.avg_latency_ms.unwrap_or(u64::MAX)))
+    });
+
+    print_ranking(&results, total);
+
+    Ok(results)
+}
+
+fn print_ranking(results: &[CleanIpResult], total: usize) {
+    let clean: Vec<_> = results.iter().filter(|r| r.successes > 0).collect();
+
+    outln!("\n{}", "═".repeat(60).cyan());
+    ui::center_text("CLEAN IP RANKING");
+    outln!("{}", "═".repeat(60).cyan());
+
+    if clean.is_empty() {
+        outln!("\n{}", "⚠️  Tidak ada IP yang lolos TLS handshake".yellow());
+    } else {
+        outln!(
+            "\n  {:<4} {:<40} {:>8} {:>10}",
+            "#".bright_black(),
+            "IP".bright_black(),
+            "Success".bright_black(),
+            "Latency".bright_black()
+        );
+        for (idx, result) in clean.iter().enumerate().take(20) {
+            let rate = format!("{:.0}%", result.success_rate() * 100.0);
+            let latency = format!("{} ms", result.avg_latency_ms.unwrap_or(0));
+            let rate = if result.successes == result.attempts { rate.green() } else { rate.yellow() };
+            outln!(
+                "  {:<4} {:<40} {:>8} {:>10}",
+                (idx + 1).to_string().cyan(),
+                result.ip.to_string().green(),
+                rate,
+                latency
+            );
+        }
+        if clean.len() > 20 {
+            outln!("  ... dan {} lagi", clean.len() - 20);
+        }
+    }
+
+    outln!("\n{}", "─".repeat(60).bright_black());
+    outln!("Statistik:");
+    outln!("  Scanned: {}/{} IP", results.len(), total);
+    outln!(
+        "  Clean: {} | Failed: {}",
+        clean.len().to_string().green(),
+        (results.len() - clean.len()).to_string().red()
+    );
+    outln!("{}", "─".repeat(60).bright_black());
+}
+
+/// Konversi ke ScanResult supaya bisa diexport seperti scan biasa
+pub fn to_scan_results(results: &[CleanIpResult], options: &ScanOptions) -> Vec<ScanResult> {
+    results
+        .iter()
+        .map(|r| {
+            let ip = r.ip.to_string();
+            let error_msg = if r.successes == r.attempts {
+                None
+            } else {
+                Some(format!(
+                    "Success {}/{}{}",
+                    r.successes,
+                    r.attempts,
+                    r.last_error.as_ref().map(|e| format!(" ({})", e)).unwrap_or_default()
+                ))
+            };
+
+            ScanResult {
+                subdomain: ip.clone(),
+                ip: ip.clone(),
+                port: Some(options.port()),
+                provider: r.provider,
+                cname_chain: Vec::new(),
+                cname_provider: None,
+                wildcard: None,
+                is_working: r.successes > 0,
+                status_code: None,
+                server: None,
+                cf_ray: None,
+                body_size: None,
+                latency_ms: r.avg_latency_ms,
+                error_msg: error_msg.clone(),
+                retryable: false,
+                ip_results: vec![IpResult {
+                    ip,
+                    provider: r.provider,
+                    is_working: r.successes > 0,
+                    status_code: None,
+                    latency_ms: r.avg_latency_ms,
+                    error_msg,
+                }],
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(cidrs: &str, sample: usize, sweep: bool) -> CleanIpOptions {
+        CleanIpOptions {
+            cidrs: parse_cidrs(cidrs).unwrap(),
+            sample,
+            sweep,
+            attempts: 1,
+        }
+    }
+
+    #[test]
+    fn parse_cidrs_accepts_commas_spaces_and_single_ips() {
+        let cidrs = parse_cidrs("104.16.0.0/20, 1.1.1.1  2606:4700::/32").unwrap();
+        assert_eq!(cidrs.len(), 3);
+        assert_eq!(cidrs[1].prefix(), 32);
+        assert!(cidrs[2].is_ipv6());
+        assert!(parse_cidrs("1.1.1.0/33").is_err());
+        assert!(parse_cidrs("bukan-cidr").is_err());
+        assert!(parse_cidrs("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn expand_targets_sweep_and_small_ranges() {
+        let targets = expand_targets(&options("10.0.0.0/30", 10, true)).unwrap();
+        let expected: Vec<IpAddr> = (0..4).map(|i| IpAddr::V4(Ipv4Addr::new(10, 0, 0, i))).collect();
+        assert_eq!(targets, expected);
+
+        // Sample >= ukuran CIDR: semua alamat dites
+        assert_eq!(expand_targets(&options("10.0.0.0/30", 10, false)).unwrap(), expected);
+        assert!(expand_targets(&options("10.0.0.0/8", 10, true)).is_err());
+    }
+
+    #[test]
+    fn expand_targets_samples_distinct_addresses_in_range() {
+        let opts = options("10.1.0.0/16, 2001:db8::/32", 50, false);
+        let targets = expand_targets(&opts).unwrap();
+        assert_eq!(targets.len(), 100);
+        assert_eq!(targets.iter().collect::<HashSet<_>>().len(), 100);
+        for ip in &targets {
+            assert!(opts.cidrs.iter().any(|net| net.contains(*ip)));
+        }
+    }
+
+    #[test]
+    fn expand_targets_bounds_sample() {
+        assert!(expand_targets(&options("2001:db8::/32", 0, false)).is_err());
+        assert!(expand_targets(&options("2001:db8::/32", MAX_SAMPLE_PER_CIDR + 1, false)).is_err());
+        // Sweep mengabaikan sample
+        assert_eq!(expand_targets(&options("10.0.0.0/31", 0, true)).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,580 @@
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use crate::config::{Config, Profile};
+use crate::dns::{self, IpFamily};
+use crate::history::HistoryQuery;
+use crate::results::ExportFormat;
+use crate::scanner::{ProbeMode, ScanOptions};
+
+#[derive(Parser, Debug)]
+#[command(name = "InjectTools")]
+#[command(author = "hoshiyomi_id <t.me/hoshiyomi_id>")]
+#[command(version)]
+#[command(about = "Bug Inject Scanner for Cloudflare Subdomains", long_about = None)]
+#[command(after_help = "Tanpa subcommand = menu interaktif (flag probe/batch di atas jadi default menu)")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Output JSON per host di stdout (log ke stderr)
+    #[arg(long, global = true, conflicts_with = "quiet")]
+    pub json: bool,
+
+    /// Hanya tulis host working di stdout, tanpa log
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Path config.toml (default: <home>/config.toml, home dari INJECTTOOLS_HOME,
+    /// /sdcard/InjectTools, atau ~/.local/share/injecttools)
+    #[arg(long, global = true, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Pakai profile target dari config.toml ([profiles.<NAME>])
+    #[arg(short, long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// DNS server, pisah koma: system, IP[:port], udp://, tcp://, tls://HOST, https://HOST/dns-query
+    /// (default: scan.resolvers di config, atau resolver bawaan)
+    #[arg(long = "resolver", global = true, value_name = "SPEC", value_delimiter = ',')]
+    pub resolvers: Vec<String>,
+
+    #[command(flatten)]
+    pub probe: ProbeArgs,
+
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+impl Cli {
+    /// Cli::parse, tapi flag probe/batch di root ditolak kalau ada subcommand
+    /// (flag root hanya default menu interaktif, subcommand punya flag sendiri)
+    pub fn parse_args() -> Self {
+        Self::try_parse_args_from(std::env::args_os()).unwrap_or_else(|e| e.exit())
+    }
+
+    pub fn try_parse_args_from<I, T>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let mut command = Self::command();
+        let matches = command.try_get_matches_from_mut(args)?;
+        let cli = Self::from_arg_matches(&matches)?;
+
+        if let Some((name, _)) = matches.subcommand() {
+            let root = BatchArgs::augment_args(ProbeArgs::augment_args(clap::Command::new("root")));
+            let misplaced: Vec<String> = root
+                .get_arguments()
+                .filter(|arg| matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine))
+                .filter_map(|arg| arg.get_long().map(|long| format!("--{}", long)))
+                .collect();
+            if !misplaced.is_empty() {
+                return Err(command.error(
+                    ErrorKind::ArgumentConflict,
+                    format!(
+                        "{} di depan subcommand hanya berlaku untuk menu interaktif, taruh setelah `{}`",
+                        misplaced.join(", "),
+                        name
+                    ),
+                ));
+            }
+        }
+
+        Ok(cli)
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Test satu subdomain ke target
+    Test {
+        /// Subdomain yang dites
+        subdomain: String,
+
+        #[command(flatten)]
+        target: TargetArg,
+
+        #[command(flatten)]
+        probe: ProbeArgs,
+    },
+
+    /// Batch test daftar host dari file/stdin atau dari export lama
+    Scan {
+        /// Daftar host dari file (satu per baris), "-" = stdin
+        #[arg(long, value_name = "FILE|-", required_unless_present = "retest")]
+        input: Option<String>,
+
+        /// Test ulang subdomain dari export lama (nomor dari `results list`, file, atau #id riwayat)
+        #[arg(long, value_name = "FILE|N|#ID", conflicts_with = "input")]
+        retest: Option<String>,
+
+        /// Dengan --retest: hanya host yang working di scan lama
+        #[arg(long, requires = "retest")]
+        working_only: bool,
+
+        /// Label domain untuk export (default: "input")
+        #[arg(short, long)]
+        domain: Option<String>,
+
+        #[command(flatten)]
+        target: TargetArg,
+
+        #[command(flatten)]
+        probe: ProbeArgs,
+
+        #[command(flatten)]
+        batch: BatchArgs,
+    },
+
+    /// Test banyak target sekaligus: matrix subdomain × target
+    Matrix {
+        /// Daftar host dari file (satu per baris), "-" = stdin
+        #[arg(long, value_name = "FILE|-")]
+        input: String,
+
+        /// Target host, dipisah koma atau diulang (-t a -t b)
+        #[arg(short = 't', long = "target", value_name = "TARGET", value_delimiter = ',')]
+        targets: Vec<String>,
+
+        /// Profile dari config.toml sebagai target (opsi probe ikut profile)
+        #[arg(long, value_name = "NAME", value_delimiter = ',')]
+        profiles: Vec<String>,
+
+        /// Semua profile di config.toml
+        #[arg(long, conflicts_with = "profiles")]
+        all_profiles: bool,
+
+        /// Label domain untuk export (default: "input")
+        #[arg(short, long)]
+        domain: Option<String>,
+
+        #[command(flatten)]
+        probe: ProbeArgs,
+
+        #[command(flatten)]
+        batch: BatchArgs,
+    },
+
+    /// Bandingkan jawaban DNS beberapa resolver (hijack, sinkhole, beda edge CDN)
+    DnsCompare {
+        /// Daftar host dari file (satu per baris), "-" = stdin
+        #[arg(long, value_name = "FILE|-")]
+        input: String,
+
+        /// Resolver yang dibandingkan, pisah koma (format sama dengan --resolver)
+        #[arg(long, value_name = "SPEC", value_delimiter = ',', default_value = "system,1.1.1.1,8.8.8.8")]
+        resolvers: Vec<String>,
+
+        /// Probe tiap IP berbeda ke target
+        #[arg(long)]
+        probe_answers: bool,
+
+        /// Label domain untuk export (default: "input")
+        #[arg(short, long)]
+        domain: Option<String>,
+
+        #[command(flatten)]
+        target: TargetArg,
+
+        #[command(flatten)]
+        probe: ProbeArgs,
+
+        #[command(flatten)]
+        batch: BatchArgs,
+    },
+
+    /// Cari subdomain (crt.sh / wordlist) lalu test
+    Discover {
+        /// Domain yang dicari subdomainnya
+        domain: String,
+
+        /// Sumber subdomain
+        #[arg(long, value_enum, default_value = "crtsh")]
+        source: DiscoverSource,
+
+        /// File wordlist (otomatis --source wordlist, default: wordlist aktif)
+        #[arg(long, value_name = "FILE")]
+        wordlist: Option<String>,
+
+        /// Hanya tampilkan subdomain yang ditemukan, tanpa test
+        #[arg(long)]
+        list_only: bool,
+
+        #[command(flatten)]
+        target: TargetArg,
+
+        #[command(flatten)]
+        probe: ProbeArgs,
+
+        #[command(flatten)]
+        batch: BatchArgs,
+    },
+
+    /// Scan clean IP CDN (TLS handshake dengan SNI target)
+    Cleanip {
+        /// CIDR dipisah koma (default: range Cloudflare)
+        #[arg(long)]
+        cidr: Option<String>,
+
+        /// Jumlah IP acak per CIDR (1-65536; CIDR yang lebih kecil dites semua)
+        #[arg(long, default_value = "10")]
+        sample: usize,
+
+        /// Test semua IP di tiap CIDR (maksimal /16 per CIDR)
+        #[arg(long)]
+        sweep: bool,
+
+        /// Jumlah handshake per IP untuk success rate
+        #[arg(long, default_value = "3")]
+        attempts: u32,
+
+        #[command(flatten)]
+        target: TargetArg,
+
+        #[command(flatten)]
+        probe: ProbeArgs,
+
+        #[command(flatten)]
+        batch: BatchArgs,
+    },
+
+    /// Target host (tunnel/proxy)
+    Target {
+        #[command(subcommand)]
+        command: TargetCommand,
+    },
+
+    /// Hasil scan yang sudah diexport
+    Results {
+        #[command(subcommand)]
+        command: ResultsCommand,
+    },
+
+    /// Query riwayat scan
+    History {
+        /// alive, stopped, scans
+        #[arg(value_enum)]
+        query: HistoryQuery,
+
+        /// Rentang hari untuk query alive
+        #[arg(long, default_value = "7")]
+        days: u32,
+
+        #[command(flatten)]
+        target: TargetArg,
+    },
+
+    /// Baca / ubah config.toml
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+
+    /// Wordlist brute-force (tanpa subcommand = Wordlist Manager)
+    Wordlist {
+        #[command(subcommand)]
+        command: Option<WordlistCommand>,
+    },
+
+    /// CDN range database
+    Ranges {
+        #[command(subcommand)]
+        command: RangesCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TargetCommand {
+    /// Cek koneksi ke target (default: target di config)
+    Check {
+        /// Target host
+        target: Option<String>,
+
+        /// Timeout in seconds [default: profile / scan.timeout di config]
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        timeout: Option<u64>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ResultsCommand {
+    /// Daftar file export
+    List,
+
+    /// Tampilkan satu export (default: terbaru)
+    Show {
+        /// Nomor dari `results list`, nama file, atau path
+        file: Option<String>,
+    },
+
+    /// Bandingkan dua scan (nomor dari `results list`, file export, atau #id riwayat)
+    Diff {
+        /// Scan lama: nomor, nama file, path, atau #id
+        old: String,
+        /// Scan baru: nomor, nama file, path, atau #id
+        new: String,
+
+        /// Tulis hasil diff sebagai JSON
+        #[arg(long, value_name = "FILE")]
+        json_out: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Tampilkan config (semua atau satu key)
+    Get { key: Option<String> },
+
+    /// Ubah satu key: target_host, timeout, active_wordlist, active_profile
+    Set { key: String, value: String },
+
+    /// Tampilkan lokasi config.toml dan folder data
+    Path,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum WordlistCommand {
+    /// Daftar wordlist yang sudah didownload
+    List,
+
+    /// Set wordlist aktif (path / nama file di folder wordlist, "embedded" = bawaan)
+    Use { file: String },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RangesCommand {
+    /// Update range database dari file lokal atau URL
+    Update {
+        #[arg(value_name = "FILE|URL")]
+        source: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DiscoverSource {
+    Crtsh,
+    Wordlist,
+}
+
+#[derive(Args, Debug)]
+pub struct TargetArg {
+    /// Target host (default: profile aktif / target di config)
+    #[arg(short, long)]
+    pub target: Option<String>,
+}
+
+impl TargetArg {
+    /// Target dari flag, fallback ke config
+    pub fn resolve(&self, config_target: &str) -> anyhow::Result<String> {
+        match &self.target {
+            Some(target) => Ok(target.clone()),
+            None if !config_target.is_empty() => Ok(config_target.to_string()),
+            None => Err(anyhow::anyhow!(
+                "Target belum diset: pakai -t/--target, --profile, atau `injecttools config set target_host HOST`"
+            )),
+        }
+    }
+}
+
+/// Opsi probe per host. Flag yang tidak diisi diambil dari profile aktif
+#[derive(Args, Debug, Clone)]
+pub struct ProbeArgs {
+    /// Timeout in seconds [default: profile / scan.timeout di config]
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub timeout: Option<u64>,
+
+    /// Probe mode: tls (handshake saja), http (handshake + HTTP request), ws (WebSocket upgrade)
+    /// [default: scan.mode di config]
+    #[arg(long, value_enum)]
+    pub mode: Option<ProbeMode>,
+
+    /// WebSocket path (otomatis pakai mode ws)
+    #[arg(long)]
+    pub ws_path: Option<String>,
+
+    /// SNI untuk TLS handshake (default: target)
+    #[arg(long)]
+    pub sni: Option<String>,
+
+    /// Host header untuk HTTP/WS request (default: target)
+    #[arg(long)]
+    pub host_header: Option<String>,
+
+    /// Path untuk HTTP request [default: /]
+    #[arg(long)]
+    pub path: Option<String>,
+
+    /// Port yang dites di IP subdomain [default: profile / scan.ports di config]
+    #[arg(long, value_parser = clap::value_parser!(u16).range(1..))]
+    pub port: Option<u16>,
+
+    /// Ulangi host yang timeout / gagal connect [default: scan.retries di config]
+    #[arg(long)]
+    pub retries: Option<u32>,
+
+    /// Koneksi plain TCP tanpa TLS (http:// / ws://)
+    #[arg(long)]
+    pub no_tls: bool,
+
+    /// Probe semua IP (A/AAAA) subdomain, bukan hanya yang pertama
+    #[arg(long)]
+    pub all_ips: bool,
+
+    /// Versi IP: 4 (IPv4 saja), 6 (IPv6 saja), any (keduanya)
+    #[arg(long, value_enum, default_value = "any")]
+    pub ip_version: IpFamily,
+}
+
+/// Opsi batch scan dan export
+#[derive(Args, Debug, Clone)]
+pub struct BatchArgs {
+    /// Jumlah host yang dites bersamaan [default: scan.concurrency di config]
+    #[arg(long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub concurrency: Option<usize>,
+
+    /// Jeda sebelum tiap host, ms [default: scan.delay_ms di config]
+    #[arg(long, value_name = "MS")]
+    pub delay: Option<u64>,
+
+    /// Batas request per detik (0 = unlimited, minimal 0.01)
+    #[arg(long, default_value = "0", value_parser = parse_rps)]
+    pub rps: f64,
+
+    /// Format file export: txt, json, csv, ndjson
+    #[arg(long, value_enum, default_value = "txt")]
+    pub format: ExportFormat,
+
+    /// Jangan deteksi wildcard DNS (semua kandidat dites)
+    #[arg(long)]
+    pub no_wildcard_check: bool,
+}
+
+fn parse_rps(value: &str) -> Result<f64, String> {
+    let rps: f64 = value.parse().map_err(|_| format!("bukan angka: {}", value))?;
+    if rps == 0.0 || (rps.is_finite() && rps >= 0.01) {
+        Ok(rps)
+    } else {
+        Err("harus 0 (unlimited) atau >= 0.01".to_string())
+    }
+}
+
+impl Default for BatchArgs {
+    fn default() -> Self {
+        Self {
+            concurrency: None,
+            delay: None,
+            rps: 0.0,
+            format: ExportFormat::Txt,
+            no_wildcard_check: false,
+        }
+    }
+}
+
+impl ProbeArgs {
+    /// Flag CLI > profile aktif > [scan] di config
+    pub fn scan_options(&self, batch: &BatchArgs, config: &Config) -> ScanOptions {
+        self.scan_options_for(batch, config, config.profile())
+    }
+
+    /// Seperti scan_options, tapi dengan profile tertentu (matrix per profile)
+    pub fn scan_options_for(&self, batch: &BatchArgs, config: &Config, profile: Option<&Profile>) -> ScanOptions {
+        let profile = profile.cloned().unwrap_or_default();
+        let scan = &config.scan;
+        // ws_path profile hanya dipakai kalau --path / --mode (selain ws) tidak diisi
+        let profile_ws_path = profile
+            .ws_path
+            .clone()
+            .filter(|_| self.path.is_none() && self.mode.is_none_or(|mode| mode == ProbeMode::Ws));
+        let (mode, path) = match self.ws_path.clone().or(profile_ws_path) {
+            Some(ws_path) => (ProbeMode::Ws, ws_path),
+            None => (
+                self.mode.unwrap_or(scan.mode),
+                self.path.clone().or(profile.path).unwrap_or_else(|| "/".to_string()),
+            ),
+        };
+
+        ScanOptions {
+            timeout: self.timeout.or(profile.timeout).unwrap_or(scan.timeout),
+            concurrency: batch.concurrency.unwrap_or(scan.concurrency),
+            rate_limit: (batch.rps > 0.0).then_some(batch.rps),
+            mode,
+            sni: self.sni.clone().or(profile.sni),
+            host_header: self.host_header.clone().or(profile.host_header),
+            path,
+            ports: match self.port.or(profile.port) {
+                Some(port) => vec![port],
+                None => scan.ports.clone(),
+            },
+            delay_ms: batch.delay.unwrap_or(scan.delay_ms),
+            retries: self.retries.unwrap_or(scan.retries),
+            tls: !self.no_tls,
+            all_ips: self.all_ips,
+            ip_family: self.ip_version,
+            resolver: dns::Resolver::shared(),
+            wildcard_check: !batch.no_wildcard_check,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::try_parse_args_from(std::iter::once("injecttools").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn root_batch_flags_before_subcommand_are_rejected() {
+        let err = parse(&["--format", "json", "scan", "--input", "hosts.txt", "-t", "example.com"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+        assert!(err.to_string().contains("--format"));
+
+        let err = parse(&["--no-wildcard-check", "--timeout", "3", "scan", "--input", "-"]).unwrap_err();
+        assert!(err.to_string().contains("--timeout, --no-wildcard-check"));
+    }
+
+    #[test]
+    fn subcommand_flags_and_globals_are_accepted() {
+        let cli = parse(&["--json", "--resolver", "1.1.1.1", "scan", "--format", "json", "--no-wildcard-check", "--input", "-"])
+            .unwrap();
+        match cli.command {
+            Some(Command::Scan { batch, .. }) => {
+                assert_eq!(batch.format, ExportFormat::Json);
+                assert!(batch.no_wildcard_check);
+            }
+            other => panic!("bukan scan: {:?}", other),
+        }
+        assert_eq!(cli.resolvers, vec!["1.1.1.1"]);
+    }
+
+    #[test]
+    fn root_flags_without_subcommand_feed_the_menu() {
+        let cli = parse(&["--format", "csv", "--timeout", "4"]).unwrap();
+        assert!(cli.command.is_none());
+        assert_eq!(cli.batch.format, ExportFormat::Csv);
+        assert_eq!(cli.probe.timeout, Some(4));
+    }
+
+    #[test]
+    fn rps_rejects_values_without_a_usable_interval() {
+        assert!(parse(&["scan", "--input", "-", "--rps", "0.5"]).is_ok());
+        assert!(parse(&["scan", "--input", "-", "--rps", "0"]).is_ok());
+        for rps in ["1e-300", "-1", "NaN", "inf"] {
+            assert!(parse(&["scan", "--input", "-", "--rps", rps]).is_err(), "{}", rps);
+        }
+    }
+
+    #[test]
+    fn zero_timeout_concurrency_and_port_are_rejected() {
+        for flag in ["--timeout", "--concurrency", "--port"] {
+            assert!(parse(&["scan", "--input", "-", flag, "0"]).is_err(), "{}", flag);
+        }
+        assert!(parse(&["target", "check", "--timeout", "0"]).is_err());
+        assert!(parse(&["scan", "--input", "-", "--timeout", "1", "--concurrency", "1", "--port", "1"]).is_ok());
+    }
+}
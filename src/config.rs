@@ -1,39 +1,322 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
-#[derive(Debug, Serialize, Deserialize)]
+use clap::ValueEnum;
+
+use crate::dns;
+use crate::scanner::ProbeMode;
+
+/// Folder default di Android/Termux
+const SDCARD_HOME: &str = "/sdcard/InjectTools";
+
+/// Lokasi config.toml dan folder data (results, riwayat, ranges)
+struct Paths {
+    config: PathBuf,
+    home: PathBuf,
+}
+
+static PATHS: OnceLock<Paths> = OnceLock::new();
+
+/// Key yang bisa dibaca/diubah lewat `config get/set`
+const KEYS: &str = "target_host, active_wordlist, active_profile, scan.timeout, scan.concurrency, \
+                    scan.delay_ms, scan.retries, scan.ports, scan.mode, scan.resolvers";
+
+fn parse_number<T: std::str::FromStr>(key: &str, value: &str) -> anyhow::Result<T> {
+    value
+        .parse()
+        .map_err(|_| anyhow::anyhow!("{} harus angka: {}", key, value))
+}
+
+/// Seperti parse_number, tapi 0 ditolak (timeout, concurrency, port)
+pub fn parse_positive<T: std::str::FromStr + Default + PartialEq>(key: &str, value: &str) -> anyhow::Result<T> {
+    let number: T = parse_number(key, value)?;
+    if number == T::default() {
+        return Err(anyhow::anyhow!("{} harus lebih dari 0", key));
+    }
+    Ok(number)
+}
+
+/// Nama probe mode seperti di CLI (tls / http / ws)
+pub fn mode_name(mode: ProbeMode) -> &'static str {
+    match mode {
+        ProbeMode::Tls => "tls",
+        ProbeMode::Http => "http",
+        ProbeMode::Ws => "ws",
+    }
+}
+
+/// Tentukan lokasi sekali saat startup.
+/// Folder data: INJECTTOOLS_HOME, lalu /sdcard/InjectTools kalau bisa ditulis,
+/// lalu XDG data dir (~/.local/share/injecttools). Config: --config atau <home>/config.toml
+pub fn init_paths(config_override: Option<&Path>) -> anyhow::Result<()> {
+    let home = resolve_home()?;
+    let config = match config_override {
+        Some(path) => path.to_path_buf(),
+        None => home.join("config.toml"),
+    };
+    // Sudah diset = tidak berubah selama proses berjalan
+    let _ = PATHS.set(Paths { config, home });
+    Ok(())
+}
+
+fn paths() -> &'static Paths {
+    PATHS.get().expect("config::init_paths belum dipanggil")
+}
+
+fn resolve_home() -> anyhow::Result<PathBuf> {
+    // Override eksplisit tidak di-fallback, supaya salah path langsung ketahuan
+    if let Some(home) = std::env::var_os("INJECTTOOLS_HOME").filter(|v| !v.is_empty()) {
+        let home = PathBuf::from(home);
+        ensure_writable(&home).map_err(|e| {
+            anyhow::anyhow!("INJECTTOOLS_HOME={} tidak bisa ditulis: {}", home.display(), e)
+        })?;
+        return Ok(home);
+    }
+
+    let candidates = [
+        Some(PathBuf::from(SDCARD_HOME)),
+        dirs::data_dir().map(|dir| dir.join("injecttools")),
+    ];
+
+    let mut tried = Vec::new();
+    for dir in candidates.into_iter().flatten() {
+        match ensure_writable(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(e) => tried.push(format!("  {} ({})", dir.display(), e)),
+        }
+    }
+
+    Err(anyhow::anyhow!(
+        "Tidak ada folder data yang bisa ditulis. Sudah dicoba:\n{}\nSet INJECTTOOLS_HOME ke folder yang bisa ditulis (Termux: jalankan termux-setup-storage untuk /sdcard)",
+        tried.join("\n")
+    ))
+}
+
+// create_dir_all saja belum cukup: /sdcard bisa ada tapi read-only tanpa izin storage
+fn ensure_writable(dir: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let probe = dir.join(".write-test");
+    fs::write(&probe, b"")?;
+    fs::remove_file(probe)
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Config {
+    /// Target tanpa profile (dipakai kalau active_profile kosong)
     pub target_host: String,
+    /// Config lama: timeout di top-level, dipindah ke [scan] saat load
+    #[serde(default, rename = "timeout", skip_serializing)]
+    legacy_timeout: Option<u64>,
+    /// Path wordlist aktif (None = embedded)
+    #[serde(default)]
+    pub active_wordlist: Option<String>,
+    #[serde(default)]
+    pub active_profile: Option<String>,
+    #[serde(default)]
+    pub scan: ScanSettings,
+    /// [profiles.<nama>] per tunnel / VPS
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+    /// Override --profile, hanya untuk proses ini (tidak ikut disimpan)
+    #[serde(skip)]
+    session_profile: Option<String>,
+}
+
+/// [scan]: default scan, bisa di-override flag CLI / profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanSettings {
+    /// Timeout per host (detik), juga untuk cek status target
     pub timeout: u64,
+    pub concurrency: usize,
+    /// Jeda sebelum tiap host (ms), anti rate-limit
+    pub delay_ms: u64,
+    /// Ulangi host yang timeout / gagal connect
+    pub retries: u32,
+    /// Port yang dicoba berurutan
+    pub ports: Vec<u16>,
+    pub mode: ProbeMode,
+    /// DNS server (lihat --resolver). Kosong = default
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub resolvers: Vec<String>,
 }
 
-impl Default for Config {
+impl Default for ScanSettings {
     fn default() -> Self {
         Self {
-            target_host: String::new(),
             timeout: 10,
+            concurrency: 20,
+            delay_ms: 0,
+            retries: 0,
+            ports: vec![443],
+            mode: ProbeMode::Http,
+            resolvers: Vec::new(),
         }
     }
 }
 
+/// Satu target tunnel dengan override probe-nya sendiri.
+/// Field kosong = pakai flag CLI / default
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Profile {
+    pub target_host: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sni: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host_header: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Diisi = probe mode ws ke path ini
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ws_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+}
+
 impl Config {
-    /// Get config path - Android/Termux only
+    /// Path config.toml (lihat init_paths)
     pub fn config_path() -> PathBuf {
-        PathBuf::from("/sdcard/InjectTools/config.toml")
+        paths().config.clone()
+    }
+
+    /// Folder data: results, riwayat, ranges, wordlists
+    pub fn home_dir() -> PathBuf {
+        paths().home.clone()
     }
 
-    /// Get results directory - Android/Termux only
     pub fn results_dir() -> PathBuf {
-        PathBuf::from("/sdcard/InjectTools/results")
+        paths().home.join("results")
+    }
+
+    /// Wordlist hasil download / import (Wordlist Manager)
+    pub fn wordlist_dir() -> PathBuf {
+        paths().home.join("wordlists")
+    }
+
+    /// CDN range database hasil `ranges update`
+    pub fn ranges_path() -> PathBuf {
+        paths().home.join("cdn-ranges.txt")
+    }
+
+    /// SQLite database riwayat scan
+    pub fn history_path() -> PathBuf {
+        paths().home.join("history.db")
+    }
+
+    /// Nama profile aktif: --profile dulu, lalu active_profile
+    pub fn profile_name(&self) -> Option<&str> {
+        self.session_profile.as_deref().or(self.active_profile.as_deref())
+    }
+
+    /// Profile aktif (None = pakai target_host)
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile_name().and_then(|name| self.profiles.get(name))
+    }
+
+    /// Target efektif: dari profile aktif, fallback ke target_host
+    pub fn target(&self) -> &str {
+        match self.profile() {
+            Some(profile) => &profile.target_host,
+            None => &self.target_host,
+        }
+    }
+
+    /// Aktifkan profile (None = tanpa profile), belum disimpan
+    pub fn use_profile(&mut self, name: Option<&str>) -> anyhow::Result<()> {
+        if let Some(name) = name {
+            self.check_profile(name)?;
+        }
+        self.active_profile = name.map(str::to_string);
+        self.session_profile = None;
+        Ok(())
+    }
+
+    /// Profile untuk proses ini saja (--profile)
+    pub fn override_profile(&mut self, name: &str) -> anyhow::Result<()> {
+        self.check_profile(name)?;
+        self.session_profile = Some(name.to_string());
+        Ok(())
+    }
+
+    fn check_profile(&self, name: &str) -> anyhow::Result<()> {
+        if self.profiles.contains_key(name) {
+            return Ok(());
+        }
+        let names: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+        Err(anyhow::anyhow!(
+            "Profile tidak ditemukan: {} (tersedia: {})",
+            name,
+            if names.is_empty() { "-".to_string() } else { names.join(", ") }
+        ))
+    }
+
+    /// Nilai satu key config sebagai string
+    pub fn get(&self, key: &str) -> anyhow::Result<String> {
+        match key {
+            "target_host" => Ok(self.target_host.clone()),
+            "active_wordlist" => Ok(self.active_wordlist.clone().unwrap_or_default()),
+            "active_profile" => Ok(self.active_profile.clone().unwrap_or_default()),
+            "scan.timeout" | "timeout" => Ok(self.scan.timeout.to_string()),
+            "scan.concurrency" => Ok(self.scan.concurrency.to_string()),
+            "scan.delay_ms" => Ok(self.scan.delay_ms.to_string()),
+            "scan.retries" => Ok(self.scan.retries.to_string()),
+            "scan.ports" => Ok(self.scan.ports.iter().map(u16::to_string).collect::<Vec<_>>().join(",")),
+            "scan.mode" => Ok(mode_name(self.scan.mode).to_string()),
+            "scan.resolvers" => Ok(self.scan.resolvers.join(",")),
+            _ => Err(anyhow::anyhow!("Key tidak dikenal: {} ({})", key, KEYS)),
+        }
+    }
+
+    /// Ubah satu key config (belum disimpan)
+    pub fn set(&mut self, key: &str, value: &str) -> anyhow::Result<()> {
+        match key {
+            "target_host" => self.target_host = value.to_string(),
+            "active_wordlist" => {
+                self.active_wordlist = (!value.is_empty() && value != "embedded").then(|| value.to_string())
+            }
+            "active_profile" => self.use_profile((!value.is_empty()).then_some(value))?,
+            "scan.timeout" | "timeout" => self.scan.timeout = parse_positive(key, value)?,
+            "scan.concurrency" => self.scan.concurrency = parse_positive(key, value)?,
+            "scan.delay_ms" => self.scan.delay_ms = parse_number(key, value)?,
+            "scan.retries" => self.scan.retries = parse_number(key, value)?,
+            "scan.ports" => {
+                self.scan.ports = value
+                    .split(',')
+                    .map(|p| parse_positive(key, p.trim()))
+                    .collect::<anyhow::Result<Vec<u16>>>()?
+            }
+            "scan.mode" => {
+                self.scan.mode = ProbeMode::from_str(value, true)
+                    .map_err(|_| anyhow::anyhow!("scan.mode harus tls, http, atau ws: {}", value))?
+            }
+            "scan.resolvers" => {
+                let resolvers: Vec<String> = value
+                    .split(',')
+                    .map(|r| r.trim().to_string())
+                    .filter(|r| !r.is_empty())
+                    .collect();
+                // Validasi sekarang, bukan saat scan berikutnya
+                dns::Resolver::from_specs(&resolvers)?;
+                self.scan.resolvers = resolvers;
+            }
+            _ => return Err(anyhow::anyhow!("Key tidak dikenal: {} ({})", key, KEYS)),
+        }
+        Ok(())
     }
 
     pub fn load_or_create() -> anyhow::Result<Self> {
         let config_path = Self::config_path();
         
         if config_path.exists() {
-            let content = fs::read_to_string(&config_path)?;
-            Ok(toml::from_str(&content)?)
+            let content = fs::read_to_string(&config_path)
+                .map_err(|e| anyhow::anyhow!("Gagal membaca config {}: {}", config_path.display(), e))?;
+            Self::parse(&content)
+                .map_err(|e| anyhow::anyhow!("Config {} tidak valid: {}", config_path.display(), e))
         } else {
             let config = Self::default();
             config.save()?;
@@ -41,11 +324,25 @@ impl Config {
         }
     }
 
+    fn parse(content: &str) -> anyhow::Result<Self> {
+        let table: toml::Table = toml::from_str(content)?;
+        let has_scan = table.contains_key("scan");
+        let mut config: Self = table.try_into()?;
+
+        // Migrasi timeout top-level ke [scan]
+        if let Some(timeout) = config.legacy_timeout.take() {
+            if !has_scan {
+                config.scan.timeout = timeout;
+            }
+        }
+        Ok(config)
+    }
+
     pub fn save(&self) -> anyhow::Result<()> {
         let config_path = Self::config_path();
         
-        // Create /sdcard/InjectTools directory
-        if let Some(parent) = config_path.parent() {
+        // Folder config (bisa beda dari home kalau pakai --config)
+        if let Some(parent) = config_path.parent().filter(|p| !p.as_os_str().is_empty()) {
             fs::create_dir_all(parent)?;
         }
         
@@ -54,8 +351,55 @@ impl Config {
         fs::create_dir_all(results_dir)?;
         
         let content = toml::to_string_pretty(self)?;
-        fs::write(config_path, content)?;
+        fs::write(&config_path, content)
+            .map_err(|e| anyhow::anyhow!("Gagal menulis config {}: {}", config_path.display(), e))?;
         
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_timeout_moves_to_scan() {
+        let config = Config::parse("target_host = \"t.example.com\"\ntimeout = 7\n").unwrap();
+        assert_eq!(config.scan.timeout, 7);
+        assert_eq!(config.legacy_timeout, None);
+        // Disimpan ulang hanya di [scan]
+        let saved = toml::to_string(&config).unwrap();
+        assert!(!saved.split("[scan]").next().unwrap().contains("timeout"));
+    }
+
+    #[test]
+    fn scan_section_wins_over_legacy_timeout() {
+        let config = Config::parse("target_host = \"\"\ntimeout = 7\n\n[scan]\ntimeout = 15\n").unwrap();
+        assert_eq!(config.scan.timeout, 15);
+
+        // [scan] tanpa timeout: default, bukan timeout lama
+        let config = Config::parse("target_host = \"\"\ntimeout = 7\n\n[scan]\nretries = 2\n").unwrap();
+        assert_eq!(config.scan.timeout, ScanSettings::default().timeout);
+        assert_eq!(config.scan.retries, 2);
+    }
+
+    #[test]
+    fn config_without_timeout_uses_defaults() {
+        let config = Config::parse("target_host = \"t.example.com\"\n").unwrap();
+        assert_eq!(config.scan.timeout, ScanSettings::default().timeout);
+        assert!(Config::parse("target_host = 1\n").is_err());
+    }
+
+    #[test]
+    fn set_rejects_zero_timeout_concurrency_and_port() {
+        let mut config = Config::default();
+        for (key, value) in [("scan.timeout", "0"), ("scan.concurrency", "0"), ("scan.ports", "443,0")] {
+            assert!(config.set(key, value).is_err(), "{} = {}", key, value);
+        }
+        assert_eq!(config.scan.ports, ScanSettings::default().ports);
+
+        config.set("scan.ports", "443, 8443").unwrap();
+        assert_eq!(config.scan.ports, vec![443, 8443]);
+        config.set("scan.delay_ms", "0").unwrap();
+    }
+}
@@ -0,0 +1,334 @@
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+use crate::{out, outln};
+use crate::cdn::{self, Provider};
+use crate::history;
+use crate::results::{self, ScanMeta, ScanSet};
+use crate::scanner::ScanResult;
+use crate::ui;
+
+/// Perubahan satu subdomain antara scan lama dan baru
+#[derive(Debug, Clone, Serialize)]
+pub struct HostChange {
+    pub subdomain: String,
+    pub old_ip: String,
+    pub new_ip: String,
+    pub old_provider: Option<Provider>,
+    pub new_provider: Option<Provider>,
+    pub old_working: bool,
+    pub new_working: bool,
+    /// Error di scan baru (berguna untuk newly failing)
+    pub error_msg: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffReport {
+    pub old: String,
+    pub new: String,
+    pub old_meta: Option<ScanMeta>,
+    pub new_meta: Option<ScanMeta>,
+    pub newly_working: Vec<HostChange>,
+    pub newly_failing: Vec<HostChange>,
+    pub ip_changed: Vec<HostChange>,
+    pub provider_changed: Vec<HostChange>,
+    /// Subdomain yang hanya ada di salah satu scan
+    pub only_in_old: Vec<String>,
+    pub only_in_new: Vec<String>,
+}
+
+pub fn compare(old: &ScanSet, new: &ScanSet) -> DiffReport {
+    // Host wildcard yang di-collapse (tidak di-probe) di salah satu scan tidak dibandingkan
+    let untested: HashSet<&str> = old
+        .results
+        .iter()
+        .chain(&new.results)
+        .filter(|r| r.is_wildcard_collapsed())
+        .map(|r| r.subdomain.as_str())
+        .collect();
+    let old_results: Vec<&ScanResult> =
+        old.results.iter().filter(|r| !untested.contains(r.subdomain.as_str())).collect();
+    let new_results: Vec<&ScanResult> =
+        new.results.iter().filter(|r| !untested.contains(r.subdomain.as_str())).collect();
+
+    let old_map: HashMap<&str, &ScanResult> =
+        old_results.iter().map(|r| (r.subdomain.as_str(), *r)).collect();
+    let new_map: HashMap<&str, &ScanResult> =
+        new_results.iter().map(|r| (r.subdomain.as_str(), *r)).collect();
+
+    let mut report = DiffReport {
+        old: old.label.clone(),
+        new: new.label.clone(),
+        old_meta: old.meta.clone(),
+        new_meta: new.meta.clone(),
+        newly_working: Vec::new(),
+        newly_failing: Vec::new(),
+        ip_changed: Vec::new(),
+        provider_changed: Vec::new(),
+        only_in_old: Vec::new(),
+        only_in_new: Vec::new(),
+    };
+
+    // Urutan mengikuti scan baru
+    for after in &new_results {
+        let Some(before) = old_map.get(after.subdomain.as_str()) else {
+            report.only_in_new.push(after.subdomain.clone());
+            continue;
+        };
+
+        let change = HostChange {
+            subdomain: after.subdomain.clone(),
+            old_ip: before.ip.clone(),
+            new_ip: after.ip.clone(),
+            old_provider: before.provider,
+            new_provider: after.provider,
+            old_working: before.is_working,
+            new_working: after.is_working,
+            error_msg: after.error_msg.clone(),
+        };
+
+        if !before.is_working && after.is_working {
+            report.newly_working.push(change.clone());
+        }
+        if before.is_working && !after.is_working {
+            report.newly_failing.push(change.clone());
+        }
+        // IP kosong = DNS gagal, tidak dihitung sebagai ganti IP
+        if !before.ip.is_empty() && !after.ip.is_empty() && before.ip != after.ip {
+            report.ip_changed.push(change.clone());
+        }
+        if !before.ip.is_empty() && !after.ip.is_empty() && before.provider != after.provider {
+            report.provider_changed.push(change);
+        }
+    }
+
+    for before in &old_results {
+        if new_map.contains_key(before.subdomain.as_str()) {
+            continue;
+        }
+        // Dulu working tapi hilang dari scan baru = newly failing juga
+        if before.is_working {
+            report.newly_failing.push(HostChange {
+                subdomain: before.subdomain.clone(),
+                old_ip: before.ip.clone(),
+                new_ip: String::new(),
+                old_provider: before.provider,
+                new_provider: None,
+                old_working: true,
+                new_working: false,
+                error_msg: Some("Tidak ada di scan baru".to_string()),
+            });
+        }
+        report.only_in_old.push(before.subdomain.clone());
+    }
+
+    report
+}
+
+fn print_side(label: &str, name: &str, meta: Option<&ScanMeta>) {
+    out!("{} {}", label.bright_black(), name.cyan());
+    if let Some(meta) = meta {
+        out!(" {}", format!("({} | target {})", meta.timestamp, meta.target).bright_black());
+    }
+    outln!();
+}
+
+pub fn print_report(report: &DiffReport) {
+    outln!();
+    print_side("Lama:", &report.old, report.old_meta.as_ref());
+    print_side("Baru:", &report.new, report.new_meta.as_ref());
+
+    outln!("\n{} ({})", "✅ NEWLY WORKING".green().bold(), report.newly_working.len());
+    for change in &report.newly_working {
+        outln!(
+            "  {} {} {}",
+            "+".green(),
+            change.subdomain.green(),
+            format!("({} | {})", change.new_ip, cdn::provider_name(change.new_provider)).bright_black()
+        );
+    }
+
+    outln!("\n{} ({})", "❌ NEWLY FAILING".red().bold(), report.newly_failing.len());
+    for change in &report.newly_failing {
+        outln!(
+            "  {} {} {}",
+            "-".red(),
+            change.subdomain.red(),
+            change.error_msg.as_deref().unwrap_or("").bright_black()
+        );
+    }
+
+    outln!("\n{} ({})", "🔄 IP CHANGED".yellow().bold(), report.ip_changed.len());
+    for change in &report.ip_changed {
+        outln!(
+            "  {} {} {} → {}",
+            "~".yellow(),
+            change.subdomain,
+            change.old_ip.bright_black(),
+            change.new_ip.yellow()
+        );
+    }
+
+    outln!("\n{} ({})", "🏷️  PROVIDER CHANGED".yellow().bold(), report.provider_changed.len());
+    for change in &report.provider_changed {
+        outln!(
+            "  {} {} {} → {}",
+            "~".yellow(),
+            change.subdomain,
+            cdn::provider_name(change.old_provider).bright_black(),
+            cdn::provider_name(change.new_provider).yellow()
+        );
+    }
+
+    print_hosts("Hanya di scan lama", &report.only_in_old);
+    print_hosts("Hanya di scan baru", &report.only_in_new);
+
+    outln!("\n{}", "─".repeat(60).bright_black());
+    outln!(
+        "Hanya di scan lama: {} | Hanya di scan baru: {}",
+        report.only_in_old.len().to_string().yellow(),
+        report.only_in_new.len().to_string().yellow()
+    );
+    outln!("{}", "─".repeat(60).bright_black());
+}
+
+fn print_hosts(title: &str, hosts: &[String]) {
+    if hosts.is_empty() {
+        return;
+    }
+    outln!("\n{} ({})", title.bold(), hosts.len());
+    for host in hosts {
+        outln!("  {} {}", "·".bright_black(), host);
+    }
+}
+
+/// Bandingkan dua scan, tampilkan, dan opsional tulis JSON
+pub fn run(old: &str, new: &str, json_out: Option<&str>) -> anyhow::Result<()> {
+    let old = ScanSet::load(old)?;
+    let new = ScanSet::load(new)?;
+    // Txt lama tidak memuat host gagal: host yang berhenti working akan salah masuk "hanya di scan lama"
+    for set in [&old, &new] {
+        if set.partial {
+            return Err(anyhow::anyhow!(
+                "{} adalah laporan txt lama tanpa host gagal, tidak bisa di-diff. Pakai export json/csv/ndjson atau #id riwayat",
+                set.label
+            ));
+        }
+    }
+    let report = compare(&old, &new);
+
+    print_report(&report);
+
+    if let Some(path) = json_out {
+        fs::write(path, serde_json::to_string_pretty(&report)?)?;
+        outln!("\n{} {}", "📁 Diff JSON:".green(), path);
+    }
+
+    Ok(())
+}
+
+pub fn diff_menu() -> anyhow::Result<()> {
+    ui::print_header("DIFF SCAN RESULTS");
+
+    let files = results::list_exports()?;
+    if !files.is_empty() {
+        outln!("\n{}", "File export:".bold());
+        for (idx, path) in files.iter().enumerate().take(10) {
+            outln!(
+                "{}. {}",
+                (idx + 1).to_string().cyan(),
+                path.file_name().unwrap_or_default().to_string_lossy().green()
+            );
+        }
+    }
+
+    if let Ok(conn) = history::open() {
+        let scans = history::recent_scans(&conn, "", 5)?;
+        if !scans.is_empty() {
+            outln!("\n{}", "Riwayat:".bold());
+            for scan in scans {
+                outln!(
+                    "{} {} {} → {}",
+                    format!("#{}", scan.id).cyan(),
+                    scan.started_at.bright_black(),
+                    scan.domain,
+                    scan.target
+                );
+            }
+        }
+    }
+
+    // Nomor = file dari daftar di atas, #id = riwayat, selain itu path
+    out!("\nScan lama (nomor / #id / path): ");
+    let old = ui::read_line();
+    out!("Scan baru (nomor / #id / path): ");
+    let new = ui::read_line();
+    if old.is_empty() || new.is_empty() {
+        outln!("\n{}", "⚠️  Dua scan harus dipilih".yellow());
+        return Ok(());
+    }
+
+    out!("Simpan diff sebagai JSON? (path, kosong = tidak): ");
+    let json_out = ui::read_line();
+
+    run(&old, &new, (!json_out.is_empty()).then_some(json_out.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(label: &str, hosts: &[(&str, bool)]) -> ScanSet {
+        let results = hosts
+            .iter()
+            .map(|(host, working)| {
+                let mut result = ScanResult::dns_failed(host, String::new());
+                result.ip = "104.16.1.1".to_string();
+                result.is_working = *working;
+                result
+            })
+            .collect();
+        ScanSet {
+            label: label.to_string(),
+            meta: None,
+            results,
+            partial: false,
+        }
+    }
+
+    #[test]
+    fn working_host_missing_from_new_scan_is_newly_failing() {
+        let old = set("a", &[("bug.example.com", true), ("dead.example.com", false)]);
+        let new = set("b", &[("fresh.example.com", true)]);
+        let report = compare(&old, &new);
+
+        let failing: Vec<&str> = report.newly_failing.iter().map(|c| c.subdomain.as_str()).collect();
+        assert_eq!(failing, vec!["bug.example.com"]);
+        assert_eq!(report.only_in_old, vec!["bug.example.com", "dead.example.com"]);
+        assert_eq!(report.only_in_new, vec!["fresh.example.com"]);
+    }
+
+    #[test]
+    fn wildcard_collapsed_hosts_are_not_compared() {
+        let old = set("a", &[("a.w.example.com", true), ("b.w.example.com", true)]);
+        let mut new = set("b", &[("a.w.example.com", true)]);
+        new.results.push(ScanResult::wildcard_collapsed("b.w.example.com", "w.example.com"));
+        let report = compare(&old, &new);
+
+        assert!(report.newly_failing.is_empty());
+        assert!(report.only_in_old.is_empty() && report.only_in_new.is_empty());
+    }
+
+    #[test]
+    fn status_changes_between_scans() {
+        let old = set("a", &[("up.example.com", false), ("down.example.com", true)]);
+        let new = set("b", &[("up.example.com", true), ("down.example.com", false)]);
+        let report = compare(&old, &new);
+
+        assert_eq!(report.newly_working[0].subdomain, "up.example.com");
+        assert_eq!(report.newly_failing[0].subdomain, "down.example.com");
+        assert!(report.only_in_old.is_empty() && report.only_in_new.is_empty());
+    }
+}
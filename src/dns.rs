@@ -1,60 +1,380 @@
 use trust_dns_resolver::TokioAsyncResolver;
+use trust_dns_resolver::lookup::Lookup;
 use trust_dns_resolver::config::*;
-use ipnetwork::Ipv4Network;
-use std::net::Ipv4Addr;
+use trust_dns_resolver::system_conf;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
 
-// Cloudflare IP ranges
-const CLOUDFLARE_RANGES: &[&str] = &[
-    "173.245.48.0/20",
-    "103.21.244.0/22",
-    "103.22.200.0/22",
-    "103.31.4.0/22",
-    "141.101.64.0/18",
-    "108.162.192.0/18",
-    "190.93.240.0/20",
-    "188.114.96.0/20",
-    "197.234.240.0/22",
-    "198.41.128.0/17",
-    "162.158.0.0/15",
-    "104.16.0.0/13",
-    "104.24.0.0/14",
-    "172.64.0.0/13",
-    "131.0.72.0/22",
-];
-
-pub fn is_cloudflare_ip(ip: &str) -> bool {
-    let parsed_ip = match Ipv4Addr::from_str(ip) {
-        Ok(addr) => addr,
-        Err(_) => return false,
-    };
+/// Versi IP yang di-resolve dan dites
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, clap::ValueEnum)]
+pub enum IpFamily {
+    /// IPv4 saja (A record)
+    #[value(name = "4")]
+    V4,
+    /// IPv6 saja (AAAA record)
+    #[value(name = "6")]
+    V6,
+    /// A dan AAAA
+    #[default]
+    Any,
+}
+
+impl IpFamily {
+    fn allows(self, ip: &IpAddr) -> bool {
+        match self {
+            IpFamily::V4 => ip.is_ipv4(),
+            IpFamily::V6 => ip.is_ipv6(),
+            IpFamily::Any => true,
+        }
+    }
+}
+
+/// Jawaban DNS: IP plus rantai CNAME yang dilewati
+#[derive(Debug, Clone, Default)]
+pub struct Answer {
+    pub ips: Vec<String>,
+    /// Target CNAME berurutan dari nama yang di-query, tanpa titik di akhir
+    pub cnames: Vec<String>,
+}
+
+struct CacheEntry {
+    answer: Answer,
+    valid_until: Instant,
+}
+
+/// Resolver bersama: satu TokioAsyncResolver untuk seluruh proses, dengan cache
+/// yang mengikuti TTL record. Dipakai scanner lewat ScanOptions::resolver
+pub struct Resolver {
+    inner: TokioAsyncResolver,
+    /// Server yang dipakai, untuk metadata scan
+    label: String,
+    cache: Mutex<HashMap<(String, IpFamily), CacheEntry>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl std::fmt::Debug for Resolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (hits, misses) = self.stats();
+        f.debug_struct("Resolver").field("label", &self.label).field("hits", &hits).field("misses", &misses).finish()
+    }
+}
+
+static SHARED: OnceLock<Arc<Resolver>> = OnceLock::new();
+
+impl Resolver {
+    fn new(config: ResolverConfig, label: String) -> Self {
+        let mut opts = ResolverOpts::default();
+        opts.ip_strategy = LookupIpStrategy::Ipv4AndIpv6;
+
+        Self {
+            inner: TokioAsyncResolver::tokio(config, opts),
+            label,
+            cache: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Resolver dari daftar --resolver (lihat parse_servers). Kosong = default
+    pub fn from_specs(specs: &[String]) -> anyhow::Result<Self> {
+        if specs.is_empty() {
+            return Ok(Self::new(ResolverConfig::default(), "default".to_string()));
+        }
+
+        let mut servers = Vec::new();
+        for spec in specs {
+            servers.extend(parse_servers(spec)?);
+        }
+        let config = ResolverConfig::from_parts(None, vec![], NameServerConfigGroup::from(servers));
+        Ok(Self::new(config, specs.join(", ")))
+    }
+
+    /// Pasang resolver bersama. Harus dipanggil sebelum shared() pertama kali
+    pub fn init(specs: &[String]) -> anyhow::Result<()> {
+        let resolver = Arc::new(Self::from_specs(specs)?);
+        SHARED
+            .set(resolver)
+            .map_err(|_| anyhow::anyhow!("Resolver sudah diinisialisasi"))
+    }
+
+    /// Instance bersama, dibuat sekali saat pertama dipakai
+    pub fn shared() -> Arc<Resolver> {
+        SHARED
+            .get_or_init(|| Arc::new(Resolver::new(ResolverConfig::default(), "default".to_string())))
+            .clone()
+    }
 
-    for range_str in CLOUDFLARE_RANGES {
-        if let Ok(range) = Ipv4Network::from_str(range_str) {
-            if range.contains(parsed_ip) {
-                return true;
+    /// Server yang dipakai, mis. "default" atau "system, https://dns.google"
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub async fn resolve(&self, domain: &str, family: IpFamily) -> anyhow::Result<Vec<String>> {
+        Ok(self.resolve_answer(domain, family).await?.ips)
+    }
+
+    /// Seperti resolve, plus rantai CNAME
+    pub async fn resolve_answer(&self, domain: &str, family: IpFamily) -> anyhow::Result<Answer> {
+        // IP literal tidak perlu di-resolve
+        if let Ok(ip) = IpAddr::from_str(domain.trim_start_matches('[').trim_end_matches(']')) {
+            if !family.allows(&ip) {
+                return Err(anyhow::anyhow!("IP {} tidak sesuai dengan versi IP yang dipilih", ip));
             }
+            return Ok(Answer { ips: vec![ip.to_string()], cnames: Vec::new() });
         }
+
+        let key = (domain.to_lowercase(), family);
+        if let Some(entry) = self.cache.lock().unwrap().get(&key) {
+            if entry.valid_until > Instant::now() {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(entry.answer.clone());
+            }
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        let (answer, valid_until) = self.lookup(domain, family).await?;
+        self.cache.lock().unwrap().insert(key, CacheEntry { answer: answer.clone(), valid_until });
+        Ok(answer)
+    }
+
+    pub async fn resolve_first(&self, domain: &str) -> anyhow::Result<String> {
+        let ips = self.resolve(domain, IpFamily::Any).await?;
+        ips.first()
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("No IP found for domain"))
+    }
+
+    // Query ke DNS server, return IP + CNAME + batas TTL terpendek
+    async fn lookup(&self, domain: &str, family: IpFamily) -> anyhow::Result<(Answer, Instant)> {
+        let (ips, lookup): (Vec<String>, Lookup) = match family {
+            IpFamily::V4 => {
+                let response = self.inner.ipv4_lookup(domain).await?;
+                (response.iter().map(|a| a.0.to_string()).collect(), response.as_lookup().clone())
+            }
+            IpFamily::V6 => {
+                let response = self.inner.ipv6_lookup(domain).await?;
+                (response.iter().map(|aaaa| aaaa.0.to_string()).collect(), response.as_lookup().clone())
+            }
+            IpFamily::Any => {
+                let response = self.inner.lookup_ip(domain).await?;
+                (response.iter().map(|ip| ip.to_string()).collect(), response.as_lookup().clone())
+            }
+        };
+
+        // CNAME ikut di answer section (preserve_intermediates), urut sesuai rantai.
+        // Ipv4AndIpv6 menggabungkan lookup A dan AAAA, jadi tiap hop bisa muncul dua kali
+        let mut seen = HashSet::new();
+        let cnames = lookup
+            .records()
+            .iter()
+            .filter_map(|record| record.data().and_then(|data| data.as_cname()))
+            .map(|cname| cname.0.to_utf8().trim_end_matches('.').to_lowercase())
+            .filter(|name| seen.insert(name.clone()))
+            .collect();
+
+        Ok((Answer { ips, cnames }, lookup.valid_until()))
     }
 
-    false
+    /// (cache hit, cache miss)
+    pub fn stats(&self) -> (u64, u64) {
+        (self.hits.load(Ordering::Relaxed), self.misses.load(Ordering::Relaxed))
+    }
 }
 
-pub async fn resolve_domain(domain: &str) -> anyhow::Result<Vec<String>> {
-    let resolver = TokioAsyncResolver::tokio(
-        ResolverConfig::default(),
-        ResolverOpts::default(),
-    );
+/// Parse satu entri --resolver:
+/// - `default`: resolver bawaan (Google)
+/// - `system`: /etc/resolv.conf (atau $PREFIX/etc/resolv.conf di Termux)
+/// - `IP[:port]`: UDP + TCP, `udp://` / `tcp://` untuk satu protokol saja
+/// - `tls://host[:853]`: DNS-over-TLS
+/// - `https://host[:443][/dns-query]`: DNS-over-HTTPS
+///
+/// DoT/DoH dengan IP butuh nama sertifikat: `tls://1.1.1.1#cloudflare-dns.com`
+fn parse_servers(spec: &str) -> anyhow::Result<Vec<NameServerConfig>> {
+    let spec = spec.trim();
+    match spec.to_lowercase().as_str() {
+        "default" => return Ok(ResolverConfig::default().name_servers().to_vec()),
+        "system" => return system_servers(),
+        _ => {}
+    }
+
+    let (scheme, rest) = match spec.split_once("://") {
+        Some((scheme, rest)) => (scheme.to_lowercase(), rest),
+        None => (String::new(), spec),
+    };
+    let (rest, tls_name) = match rest.split_once('#') {
+        Some((rest, name)) => (rest, Some(name.to_string())),
+        None => (rest, None),
+    };
+    let (host_port, path) = match rest.split_once('/') {
+        Some((host_port, path)) => (host_port, path),
+        None => (rest, ""),
+    };
+
+    let (protocols, default_port): (&[Protocol], u16) = match scheme.as_str() {
+        "" => (&[Protocol::Udp, Protocol::Tcp], 53),
+        "udp" => (&[Protocol::Udp], 53),
+        "tcp" => (&[Protocol::Tcp], 53),
+        "tls" => (&[Protocol::Tls], 853),
+        "https" => (&[Protocol::Https], 443),
+        other => anyhow::bail!("Resolver '{}': skema '{}' tidak didukung (udp, tcp, tls, https)", spec, other),
+    };
+    let encrypted = matches!(scheme.as_str(), "tls" | "https");
+
+    // Endpoint DoH selalu /dns-query
+    if !path.is_empty() && (scheme != "https" || path.trim_end_matches('/') != "dns-query") {
+        anyhow::bail!("Resolver '{}': path harus /dns-query (atau kosong)", spec);
+    }
+    if tls_name.is_some() && !encrypted {
+        anyhow::bail!("Resolver '{}': #nama hanya untuk tls:// dan https://", spec);
+    }
+
+    let (host, port) = split_host_port(host_port, default_port)
+        .ok_or_else(|| anyhow::anyhow!("Resolver '{}': alamat tidak valid", spec))?;
 
-    let response = resolver.lookup_ip(domain).await?;
-    let ips: Vec<String> = response.iter().map(|ip| ip.to_string()).collect();
-    
-    Ok(ips)
+    let (addrs, tls_name) = match IpAddr::from_str(host) {
+        Ok(ip) => {
+            if encrypted && tls_name.is_none() {
+                anyhow::bail!(
+                    "Resolver '{}': DoT/DoH dengan IP butuh nama sertifikat, contoh {}://{}#dns.example",
+                    spec,
+                    scheme,
+                    host
+                );
+            }
+            (vec![SocketAddr::new(ip, port)], tls_name)
+        }
+        Err(_) if encrypted => {
+            // Bootstrap hostname DoT/DoH lewat resolver sistem
+            let addrs: Vec<SocketAddr> = (host, port)
+                .to_socket_addrs()
+                .map_err(|e| anyhow::anyhow!("Resolver '{}': gagal resolve {}: {}", spec, host, e))?
+                .collect();
+            (addrs, tls_name.or_else(|| Some(host.to_string())))
+        }
+        Err(_) => anyhow::bail!("Resolver '{}': server UDP/TCP harus berupa IP", spec),
+    };
+
+    Ok(addrs
+        .iter()
+        .flat_map(|addr| {
+            protocols.iter().map(|protocol| {
+                let mut server = NameServerConfig::new(*addr, *protocol);
+                server.tls_dns_name = tls_name.clone();
+                server
+            })
+        })
+        .collect())
 }
 
-pub async fn resolve_domain_first(domain: &str) -> anyhow::Result<String> {
-    let ips = resolve_domain(domain).await?;
-    ips.first()
-        .cloned()
-        .ok_or_else(|| anyhow::anyhow!("No IP found for domain"))
-}
\ No newline at end of file
+// "host", "host:port", "[v6]:port" atau IPv6 polos
+fn split_host_port(value: &str, default_port: u16) -> Option<(&str, u16)> {
+    if let Some(rest) = value.strip_prefix('[') {
+        let (host, port) = rest.split_once(']')?;
+        return match port.strip_prefix(':') {
+            Some(port) => Some((host, port.parse().ok()?)),
+            None if port.is_empty() => Some((host, default_port)),
+            None => None,
+        };
+    }
+    if value.matches(':').count() > 1 {
+        return Some((value, default_port));
+    }
+    match value.split_once(':') {
+        Some((host, port)) => Some((host, port.parse().ok()?)),
+        None if !value.is_empty() => Some((value, default_port)),
+        None => None,
+    }
+}
+
+// Nameserver dari resolv.conf; Termux menyimpannya di $PREFIX/etc
+fn system_servers() -> anyhow::Result<Vec<NameServerConfig>> {
+    let mut candidates = Vec::new();
+    if let Ok(prefix) = std::env::var("PREFIX") {
+        candidates.push(format!("{}/etc/resolv.conf", prefix.trim_end_matches('/')));
+    }
+    candidates.push("/etc/resolv.conf".to_string());
+
+    for path in &candidates {
+        let Ok(data) = fs::read(path) else { continue };
+        let (config, _) = system_conf::parse_resolv_conf(data)
+            .map_err(|e| anyhow::anyhow!("Gagal membaca {}: {}", path, e))?;
+        if config.name_servers().is_empty() {
+            anyhow::bail!("{} tidak berisi nameserver", path);
+        }
+        return Ok(config.name_servers().to_vec());
+    }
+
+    anyhow::bail!("resolv.conf tidak ditemukan (dicoba: {})", candidates.join(", "))
+}
+
+/// Format `ip:port`, IPv6 dibungkus bracket (`[addr]:port`)
+pub fn socket_addr_string(ip: &str, port: u16) -> String {
+    match IpAddr::from_str(ip) {
+        Ok(IpAddr::V6(addr)) => format!("[{}]:{}", addr, port),
+        _ => format!("{}:{}", ip, port),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_host_port_variants() {
+        assert_eq!(split_host_port("1.1.1.1", 53), Some(("1.1.1.1", 53)));
+        assert_eq!(split_host_port("1.1.1.1:5353", 53), Some(("1.1.1.1", 5353)));
+        assert_eq!(split_host_port("[2606:4700::1111]:853", 53), Some(("2606:4700::1111", 853)));
+        assert_eq!(split_host_port("[2606:4700::1111]", 53), Some(("2606:4700::1111", 53)));
+        assert_eq!(split_host_port("2606:4700::1111", 53), Some(("2606:4700::1111", 53)));
+        assert_eq!(split_host_port("1.1.1.1:abc", 53), None);
+        assert_eq!(split_host_port("[::1]x", 53), None);
+        assert_eq!(split_host_port("", 53), None);
+    }
+
+    #[test]
+    fn parse_servers_plain_and_udp_tcp() {
+        let servers = parse_servers("8.8.8.8").unwrap();
+        let protocols: Vec<Protocol> = servers.iter().map(|s| s.protocol).collect();
+        assert_eq!(protocols, vec![Protocol::Udp, Protocol::Tcp]);
+        assert!(servers.iter().all(|s| s.socket_addr == "8.8.8.8:53".parse().unwrap()));
+
+        let servers = parse_servers("udp://127.0.0.1:5300").unwrap();
+        assert_eq!(servers.len(), 1);
+        assert_eq!(servers[0].protocol, Protocol::Udp);
+        assert_eq!(servers[0].socket_addr, "127.0.0.1:5300".parse().unwrap());
+
+        let servers = parse_servers("TCP://[::1]").unwrap();
+        assert_eq!(servers[0].protocol, Protocol::Tcp);
+        assert_eq!(servers[0].socket_addr, "[::1]:53".parse().unwrap());
+    }
+
+    #[test]
+    fn parse_servers_encrypted_needs_name_for_ip() {
+        let servers = parse_servers("tls://1.1.1.1#cloudflare-dns.com").unwrap();
+        assert_eq!(servers[0].protocol, Protocol::Tls);
+        assert_eq!(servers[0].socket_addr, "1.1.1.1:853".parse().unwrap());
+        assert_eq!(servers[0].tls_dns_name.as_deref(), Some("cloudflare-dns.com"));
+
+        let servers = parse_servers("https://1.1.1.1/dns-query#cloudflare-dns.com").unwrap();
+        assert_eq!(servers[0].protocol, Protocol::Https);
+        assert_eq!(servers[0].socket_addr, "1.1.1.1:443".parse().unwrap());
+
+        assert!(parse_servers("tls://1.1.1.1").is_err());
+        assert!(parse_servers("udp://1.1.1.1#name").is_err());
+    }
+
+    #[test]
+    fn parse_servers_rejects_bad_specs() {
+        assert!(parse_servers("quic://1.1.1.1").is_err());
+        assert!(parse_servers("https://1.1.1.1/resolve#dns.example").is_err());
+        assert!(parse_servers("udp://1.1.1.1/dns-query").is_err());
+        assert!(parse_servers("dns.google").is_err());
+        assert!(!parse_servers("default").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,553 @@
+use chrono::Local;
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::{out, outln};
+use crate::cdn::{self, Provider};
+use crate::config::Config;
+use crate::dns;
+use crate::results::{ExportFormat, ScanMeta};
+use crate::scanner::{self, Job, ScanOptions};
+use crate::ui::{self, OutputMode};
+
+/// Resolver yang dibandingkan, satu instance per spec (tidak digabung seperti --resolver)
+pub struct CompareResolver {
+    pub spec: String,
+    pub resolver: dns::Resolver,
+}
+
+/// Buat resolver terpisah untuk tiap spec, minimal dua
+pub fn resolvers(specs: &[String]) -> anyhow::Result<Vec<CompareResolver>> {
+    if specs.len() < 2 {
+        anyhow::bail!("Butuh minimal 2 resolver untuk dibandingkan, contoh: --resolvers system,1.1.1.1");
+    }
+    specs
+        .iter()
+        .map(|spec| {
+            Ok(CompareResolver {
+                spec: spec.clone(),
+                resolver: dns::Resolver::from_specs(std::slice::from_ref(spec))?,
+            })
+        })
+        .collect()
+}
+
+/// Kesimpulan perbandingan satu host
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    /// Semua resolver menjawab IP yang sama
+    Same,
+    /// IP beda tapi CDN sama (edge / PoP lain)
+    EdgeDiffers,
+    /// CDN / pemilik IP beda antar resolver, kemungkinan hijack
+    ProviderDiffers,
+    /// Ada resolver yang menjawab IP private / 0.0.0.0 (sinkhole / blokir)
+    Sinkhole,
+    /// Sebagian resolver gagal / NXDOMAIN, yang menjawab tidak beda provider / sinkhole
+    Partial,
+    /// Semua resolver gagal
+    Failed,
+}
+
+impl Verdict {
+    pub fn label(self) -> &'static str {
+        match self {
+            Verdict::Same => "same",
+            Verdict::EdgeDiffers => "edge differs",
+            Verdict::ProviderDiffers => "provider differs",
+            Verdict::Sinkhole => "sinkhole",
+            Verdict::Partial => "partial",
+            Verdict::Failed => "failed",
+        }
+    }
+
+    /// Jawaban antar resolver tidak konsisten
+    pub fn differs(self) -> bool {
+        !matches!(self, Verdict::Same | Verdict::Failed)
+    }
+}
+
+/// Jawaban satu resolver
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolverAnswer {
+    pub resolver: String,
+    /// Terurut, kosong kalau gagal
+    pub ips: Vec<String>,
+    pub error: Option<String>,
+}
+
+/// Hasil probe satu IP berbeda (--probe-answers)
+#[derive(Debug, Clone, Serialize)]
+pub struct AnswerProbe {
+    pub ip: String,
+    pub provider: Option<Provider>,
+    /// Resolver yang menjawab IP ini
+    pub resolvers: Vec<String>,
+    pub is_working: bool,
+    pub status_code: Option<u16>,
+    pub latency_ms: Option<u64>,
+    pub error_msg: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CompareRow {
+    pub subdomain: String,
+    pub verdict: Verdict,
+    pub answers: Vec<ResolverAnswer>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub probes: Vec<AnswerProbe>,
+}
+
+impl CompareRow {
+    fn new(subdomain: &str, answers: Vec<ResolverAnswer>) -> Self {
+        Self {
+            subdomain: subdomain.to_string(),
+            verdict: verdict(&answers),
+            answers,
+            probes: Vec::new(),
+        }
+    }
+
+    /// IP unik dari semua resolver beserta resolver yang menjawabnya
+    fn distinct_ips(&self) -> BTreeMap<String, Vec<String>> {
+        let mut ips: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for answer in &self.answers {
+            for ip in &answer.ips {
+                ips.entry(ip.clone()).or_default().push(answer.resolver.clone());
+            }
+        }
+        ips
+    }
+}
+
+/// Isi file export dns-compare
+#[derive(Debug, Clone, Serialize)]
+pub struct CompareReport {
+    pub meta: ScanMeta,
+    pub resolvers: Vec<String>,
+    pub rows: Vec<CompareRow>,
+}
+
+impl CompareReport {
+    pub fn any_working(&self) -> bool {
+        self.rows.iter().any(|row| row.probes.iter().any(|p| p.is_working))
+    }
+
+    pub fn all_failed(&self) -> bool {
+        !self.rows.is_empty() && self.rows.iter().all(|row| row.verdict == Verdict::Failed)
+    }
+}
+
+// IP yang biasa dipakai untuk sinkhole / blokir DNS
+fn is_sinkhole(ip: &str) -> bool {
+    match IpAddr::from_str(ip) {
+        Ok(IpAddr::V4(ip)) => {
+            let [a, b, ..] = ip.octets();
+            ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || (a == 100 && (64..128).contains(&b))
+        }
+        Ok(IpAddr::V6(ip)) => {
+            let first = ip.segments()[0];
+            ip.is_unspecified() || ip.is_loopback() || (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80
+        }
+        Err(_) => false,
+    }
+}
+
+fn verdict(answers: &[ResolverAnswer]) -> Verdict {
+    let answered: Vec<&ResolverAnswer> = answers.iter().filter(|a| !a.ips.is_empty()).collect();
+    if answered.is_empty() {
+        return Verdict::Failed;
+    }
+
+    // Beda sinkhole / provider di antara jawaban yang ada lebih penting dari resolver yang gagal
+    let same = answered.iter().all(|a| a.ips == answered[0].ips);
+    if !same && answered.iter().any(|a| a.ips.iter().any(|ip| is_sinkhole(ip))) {
+        return Verdict::Sinkhole;
+    }
+
+    // Provider per resolver: CDN pertama yang dikenal, None = non-CDN
+    let providers: Vec<Option<Provider>> = answered
+        .iter()
+        .map(|a| a.ips.iter().find_map(|ip| cdn::lookup(ip)))
+        .collect();
+    if !same && providers.iter().any(|p| *p != providers[0]) {
+        return Verdict::ProviderDiffers;
+    }
+
+    if answered.len() < answers.len() {
+        Verdict::Partial
+    } else if same {
+        Verdict::Same
+    } else {
+        Verdict::EdgeDiffers
+    }
+}
+
+async fn resolve_with(resolver: &CompareResolver, subdomain: &str, options: &ScanOptions) -> ResolverAnswer {
+    let (ips, error) = match tokio::time::timeout(
+        Duration::from_secs(options.timeout),
+        resolver.resolver.resolve(subdomain, options.ip_family),
+    )
+    .await
+    {
+        Ok(Ok(mut ips)) if !ips.is_empty() => {
+            ips.sort();
+            ips.dedup();
+            (ips, None)
+        }
+        Ok(Ok(_)) => (Vec::new(), Some("No IP found for domain".to_string())),
+        Ok(Err(e)) => (Vec::new(), Some(e.to_string())),
+        Err(_) => (Vec::new(), Some("timeout".to_string())),
+    };
+    ResolverAnswer {
+        resolver: resolver.spec.clone(),
+        ips,
+        error,
+    }
+}
+
+// Probe tiap IP berbeda ke target, terpisah supaya terlihat IP mana yang working
+async fn probe_answers(target: &str, row: &CompareRow, options: &ScanOptions) -> Vec<AnswerProbe> {
+    let distinct = row.distinct_ips();
+    futures::future::join_all(distinct.into_iter().map(|(ip, resolvers)| async move {
+        let result = scanner::with_retries(options, || {
+            let deadline = Instant::now() + Duration::from_secs(options.timeout);
+            scanner::probe_until(deadline, target, &row.subdomain, std::slice::from_ref(&ip), options)
+        })
+        .await;
+        AnswerProbe {
+            ip,
+            provider: result.provider,
+            resolvers,
+            is_working: result.is_working,
+            status_code: result.status_code,
+            latency_ms: result.latency_ms,
+            error_msg: result.error_msg,
+        }
+    }))
+    .await
+}
+
+/// Resolve semua host lewat semua resolver bersamaan. Dengan target, tiap IP
+/// berbeda juga diprobe
+pub async fn run(
+    resolvers: &[CompareResolver],
+    subdomains: &[String],
+    target: Option<&str>,
+    options: &ScanOptions,
+    running: Arc<AtomicBool>,
+) -> Vec<CompareRow> {
+    outln!("\n{}", "Starting DNS comparison...".cyan());
+    outln!(
+        "{} {} subdomains × {} resolvers",
+        "Total:".bright_black(),
+        subdomains.len().to_string().yellow(),
+        resolvers.len().to_string().yellow()
+    );
+    if let Some(target) = target {
+        outln!("{} {}", "Probe answers ke:".bright_black(), target.yellow());
+    }
+    outln!();
+
+    let rows = scanner::worker_pool(
+        subdomains.iter().collect(),
+        options,
+        &running,
+        |subdomain| {
+            Job::Run(format!("Resolving: {}", subdomain), async move {
+                let answers =
+                    futures::future::join_all(resolvers.iter().map(|r| resolve_with(r, subdomain, options))).await;
+                let mut row = CompareRow::new(subdomain, answers);
+                if let Some(target) = target {
+                    row.probes = probe_answers(target, &row, options).await;
+                }
+                row
+            })
+        },
+        emit,
+    )
+    .await;
+
+    rows
+}
+
+// Mode --json: satu baris per host, --quiet: "subdomain verdict" yang jawabannya beda
+fn emit(row: &CompareRow) {
+    match ui::output_mode() {
+        OutputMode::Json => {
+            if let Ok(line) = serde_json::to_string(row) {
+                println!("{}", line);
+            }
+        }
+        OutputMode::Quiet => {
+            if row.verdict.differs() {
+                println!("{} {}", row.subdomain, row.verdict.label());
+            }
+        }
+        OutputMode::Text => {}
+    }
+}
+
+// IP pertama (+sisa), atau ✗ kalau gagal
+fn answer_cell(answer: &ResolverAnswer) -> String {
+    match answer.ips.len() {
+        0 => "✗".to_string(),
+        1 => answer.ips[0].clone(),
+        n => format!("{} +{}", answer.ips[0], n - 1),
+    }
+}
+
+fn colored_verdict(verdict: Verdict) -> colored::ColoredString {
+    match verdict {
+        Verdict::Same => verdict.label().green(),
+        Verdict::EdgeDiffers => verdict.label().cyan(),
+        Verdict::Failed => verdict.label().bright_black(),
+        _ => verdict.label().red(),
+    }
+}
+
+/// Tabel: baris = subdomain, kolom R1..Rn = jawaban tiap resolver
+pub fn print_table(report: &CompareReport) {
+    let width = report.rows.iter().map(|r| r.subdomain.len()).max().unwrap_or(9).clamp(9, 40);
+
+    outln!("\n{}", "═".repeat(60).cyan());
+    ui::center_text("DNS COMPARISON");
+    outln!("{}", "═".repeat(60).cyan());
+
+    outln!();
+    for (idx, resolver) in report.resolvers.iter().enumerate() {
+        outln!("{} {}", format!("R{}", idx + 1).cyan().bold(), resolver);
+    }
+
+    out!("\n{:<width$} {:<16}", "Subdomain".bold(), "Verdict".bold(), width = width);
+    for idx in 0..report.resolvers.len() {
+        out!(" {:<20}", format!("R{}", idx + 1).bold());
+    }
+    outln!();
+    outln!("{}", "─".repeat(width + 17 + report.resolvers.len() * 21).bright_black());
+
+    for row in &report.rows {
+        out!("{:<width$} {:<16}", row.subdomain, colored_verdict(row.verdict), width = width);
+        for answer in &row.answers {
+            let cell = answer_cell(answer);
+            if answer.ips.is_empty() {
+                out!(" {:<20}", cell.red());
+            } else {
+                out!(" {:<20}", cell);
+            }
+        }
+        outln!();
+
+        for probe in &row.probes {
+            let symbol = if probe.is_working { "✓".green() } else { "✗".red() };
+            outln!(
+                "  {} {} [{}] via {}{}",
+                symbol,
+                probe.ip,
+                cdn::provider_name(probe.provider),
+                probe.resolvers.join(", ").bright_black(),
+                probe.error_msg.as_ref().map(|e| format!(" - {}", e)).unwrap_or_default().bright_black()
+            );
+        }
+    }
+
+    outln!("\n{}", "─".repeat(60).bright_black());
+    let differs = report.rows.iter().filter(|r| r.verdict.differs()).count();
+    outln!(
+        "  Beda jawaban: {} dari {}",
+        if differs > 0 { differs.to_string().red() } else { differs.to_string().green() },
+        report.rows.len()
+    );
+    for verdict in [
+        Verdict::Same,
+        Verdict::EdgeDiffers,
+        Verdict::ProviderDiffers,
+        Verdict::Sinkhole,
+        Verdict::Partial,
+        Verdict::Failed,
+    ] {
+        let count = report.rows.iter().filter(|r| r.verdict == verdict).count();
+        if count > 0 {
+            outln!("  {:<17} {}", verdict.label(), count);
+        }
+    }
+    if report.rows.iter().any(|r| !r.probes.is_empty()) {
+        let working = report.rows.iter().filter(|r| r.probes.iter().any(|p| p.is_working)).count();
+        outln!("  Working (minimal satu IP): {}", working.to_string().green());
+    }
+    outln!("{}", "─".repeat(60).bright_black());
+}
+
+fn render_text(report: &CompareReport) -> String {
+    let meta = &report.meta;
+    let mut content = String::new();
+    content.push_str("InjectTools DNS Comparison\n");
+    content.push_str(&format!("Version: {}\n", meta.tool_version));
+    content.push_str(&format!("Date: {}\n", meta.timestamp));
+    content.push_str(&format!("Domain: {}\n", meta.domain));
+    content.push_str(&format!("Source: {}\n", meta.source));
+    content.push_str(&format!("Target: {}\n\n", meta.target));
+
+    for (idx, resolver) in report.resolvers.iter().enumerate() {
+        content.push_str(&format!("R{} {}\n", idx + 1, resolver));
+    }
+    content.push('\n');
+
+    for row in &report.rows {
+        content.push_str(&format!("{:<40} {}\n", row.subdomain, row.verdict.label()));
+        for (idx, answer) in row.answers.iter().enumerate() {
+            let value = match &answer.error {
+                Some(error) => format!("✗ {}", error),
+                None => answer.ips.join(" "),
+            };
+            content.push_str(&format!("  R{} {}\n", idx + 1, value));
+        }
+        for probe in &row.probes {
+            let symbol = if probe.is_working { "✓" } else { "✗" };
+            content.push_str(&format!("  {} {} [{}]\n", symbol, probe.ip, cdn::provider_name(probe.provider)));
+        }
+    }
+    content
+}
+
+// CSV lebar: satu kolom per resolver berisi IP (dipisah spasi) atau error
+fn render_csv(report: &CompareReport) -> anyhow::Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+
+    let mut header = vec!["subdomain".to_string(), "verdict".to_string()];
+    header.extend(report.resolvers.iter().cloned());
+    header.push("working_ips".to_string());
+    writer.write_record(&header)?;
+
+    for row in &report.rows {
+        let mut record = vec![row.subdomain.clone(), row.verdict.label().to_string()];
+        record.extend(row.answers.iter().map(|answer| match &answer.error {
+            Some(error) => format!("error: {}", error),
+            None => answer.ips.join(" "),
+        }));
+        let working: Vec<&str> = row.probes.iter().filter(|p| p.is_working).map(|p| p.ip.as_str()).collect();
+        record.push(working.join(" "));
+        writer.write_record(&record)?;
+    }
+
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+/// Export ke <results>/dns-compare/
+pub fn export(report: &CompareReport, format: ExportFormat) -> anyhow::Result<()> {
+    let compare_dir = Config::results_dir().join("dns-compare");
+    fs::create_dir_all(&compare_dir)?;
+
+    let timestamp = Local::now().format("%Y%m%d_%H%M%S");
+    let filename = format!(
+        "dnscompare_{}_{}.{}",
+        report.meta.domain.replace(".", "_"),
+        timestamp,
+        format.extension()
+    );
+    let filepath = compare_dir.join(&filename);
+
+    let content = match format {
+        ExportFormat::Txt => render_text(report),
+        ExportFormat::Json => serde_json::to_string_pretty(report)?,
+        ExportFormat::Ndjson => {
+            let mut content = String::new();
+            for row in &report.rows {
+                content.push_str(&serde_json::to_string(row)?);
+                content.push('\n');
+            }
+            content
+        }
+        ExportFormat::Csv => render_csv(report)?,
+    };
+
+    fs::write(&filepath, content)?;
+
+    outln!("\n{} {}", "📁 DNS comparison exported:".green(), filepath.display().to_string().bright_black());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // cdn::lookup butuh folder data; pakai folder sementara tanpa cdn-ranges.txt (= range bawaan)
+    fn init() {
+        static INIT: std::sync::Once = std::sync::Once::new();
+        INIT.call_once(|| {
+            let home = std::env::temp_dir().join(format!("injecttools-test-{}", std::process::id()));
+            std::env::set_var("INJECTTOOLS_HOME", &home);
+            crate::config::init_paths(None).unwrap();
+        });
+    }
+
+    fn answer(ips: &[&str]) -> ResolverAnswer {
+        ResolverAnswer {
+            resolver: "test".to_string(),
+            ips: ips.iter().map(|ip| ip.to_string()).collect(),
+            error: ips.is_empty().then(|| "timeout".to_string()),
+        }
+    }
+
+    #[test]
+    fn verdict_same_edge_and_provider() {
+        init();
+        let cf = answer(&["104.16.1.1"]);
+        assert_eq!(verdict(&[cf.clone(), cf.clone()]), Verdict::Same);
+        assert_eq!(verdict(&[cf.clone(), answer(&["104.17.2.2"])]), Verdict::EdgeDiffers);
+        assert_eq!(verdict(&[cf, answer(&["8.8.8.8"])]), Verdict::ProviderDiffers);
+    }
+
+    #[test]
+    fn verdict_sinkhole() {
+        init();
+        assert_eq!(verdict(&[answer(&["104.16.1.1"]), answer(&["0.0.0.0"])]), Verdict::Sinkhole);
+        assert_eq!(verdict(&[answer(&["104.16.1.1"]), answer(&["10.10.34.35"])]), Verdict::Sinkhole);
+        // Semua resolver sepakat = bukan blokir resolver tertentu
+        assert_eq!(verdict(&[answer(&["10.0.0.1"]), answer(&["10.0.0.1"])]), Verdict::Same);
+    }
+
+    #[test]
+    fn verdict_partial_only_when_answers_agree() {
+        init();
+        let failed = answer(&[]);
+        assert_eq!(verdict(&[failed.clone(), failed.clone()]), Verdict::Failed);
+        assert_eq!(verdict(&[answer(&["104.16.1.1"]), failed.clone()]), Verdict::Partial);
+        assert_eq!(
+            verdict(&[answer(&["104.16.1.1"]), answer(&["104.17.2.2"]), failed.clone()]),
+            Verdict::Partial
+        );
+        assert_eq!(
+            verdict(&[answer(&["104.16.1.1"]), answer(&["0.0.0.0"]), failed.clone()]),
+            Verdict::Sinkhole
+        );
+        assert_eq!(
+            verdict(&[answer(&["104.16.1.1"]), answer(&["8.8.8.8"]), failed]),
+            Verdict::ProviderDiffers
+        );
+    }
+
+    #[test]
+    fn sinkhole_addresses() {
+        for ip in ["0.0.0.0", "127.0.0.1", "10.1.2.3", "192.168.1.1", "100.64.0.1", "::", "::1", "fd00::1", "fe80::1"] {
+            assert!(is_sinkhole(ip), "{}", ip);
+        }
+        for ip in ["104.16.1.1", "100.128.0.1", "2606:4700::1", "bukan-ip"] {
+            assert!(!is_sinkhole(ip), "{}", ip);
+        }
+    }
+}
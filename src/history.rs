@@ -0,0 +1,438 @@
+use chrono::Local;
+use colored::Colorize;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::fs;
+
+use crate::{out, outln};
+use crate::cdn::Provider;
+use crate::config::Config;
+use crate::results::ScanMeta;
+use crate::scanner::{IpResult, ScanResult};
+use crate::ui;
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS scans (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    started_at TEXT NOT NULL,
+    tool_version TEXT NOT NULL,
+    target TEXT NOT NULL,
+    domain TEXT NOT NULL,
+    source TEXT NOT NULL,
+    timeout INTEGER NOT NULL,
+    resolver TEXT
+);
+CREATE TABLE IF NOT EXISTS results (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    scan_id INTEGER NOT NULL REFERENCES scans(id),
+    tested_at TEXT NOT NULL,
+    target TEXT NOT NULL,
+    subdomain TEXT NOT NULL,
+    ip TEXT NOT NULL,
+    provider TEXT,
+    is_working INTEGER NOT NULL,
+    status_code INTEGER,
+    latency_ms INTEGER,
+    error_msg TEXT
+);
+CREATE INDEX IF NOT EXISTS idx_results_target ON results(target, subdomain, tested_at);
+";
+
+/// Jenis query riwayat
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum HistoryQuery {
+    /// Host yang working untuk target dalam N hari terakhir
+    Alive,
+    /// Host yang dulu working tapi hasil terakhirnya gagal
+    Stopped,
+    /// Daftar scan terakhir
+    Scans,
+}
+
+/// Ringkasan satu subdomain dari riwayat
+#[derive(Debug, Clone)]
+pub struct HostRecord {
+    pub subdomain: String,
+    pub ip: String,
+    pub provider: Option<String>,
+    pub last_seen: String,
+    pub latency_ms: Option<u64>,
+    /// Alive: jumlah hasil working, Stopped: waktu terakhir working
+    pub detail: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScanRecord {
+    pub id: i64,
+    pub started_at: String,
+    pub target: String,
+    pub domain: String,
+    pub source: String,
+    pub total: u64,
+    pub working: u64,
+}
+
+pub fn open() -> anyhow::Result<Connection> {
+    let path = Config::history_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let conn = Connection::open(&path)?;
+    conn.execute_batch(SCHEMA)?;
+    migrate(&conn)?;
+    Ok(conn)
+}
+
+// Riwayat dari versi lama belum punya kolom resolver
+fn migrate(conn: &Connection) -> anyhow::Result<()> {
+    let has_resolver = conn
+        .prepare("SELECT 1 FROM pragma_table_info('scans') WHERE name = 'resolver'")?
+        .exists([])?;
+    if !has_resolver {
+        conn.execute_batch("ALTER TABLE scans ADD COLUMN resolver TEXT")?;
+    }
+    Ok(())
+}
+
+/// Simpan satu scan (batch atau single test) beserta semua hasilnya
+pub fn record_scan(meta: &ScanMeta, results: &[ScanResult]) -> anyhow::Result<i64> {
+    let mut conn = open()?;
+    let tx = conn.transaction()?;
+
+    tx.execute(
+        "INSERT INTO scans (started_at, tool_version, target, domain, source, timeout, resolver)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            meta.timestamp,
+            meta.tool_version,
+            meta.target,
+            meta.domain,
+            meta.source,
+            meta.timeout,
+            meta.resolver
+        ],
+    )?;
+    let scan_id = tx.last_insert_rowid();
+
+    {
+        let mut insert = tx.prepare(
+            "INSERT INTO results
+             (scan_id, tested_at, target, subdomain, ip, provider, is_working, status_code, latency_ms, error_msg)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        )?;
+        // Host wildcard yang tidak di-probe bukan hasil test, jangan sampai terbaca "stopped"
+        for result in results.iter().filter(|r| !r.is_wildcard_collapsed()) {
+            insert.execute(params![
+                scan_id,
+                meta.timestamp,
+                meta.target,
+                result.subdomain,
+                result.ip,
+                result.provider.map(|p| p.name()),
+                result.is_working,
+                result.status_code,
+                result.latency_ms,
+                result.error_msg,
+            ])?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(scan_id)
+}
+
+/// Seperti record_scan, tapi gagal simpan riwayat hanya jadi warning
+pub fn record_or_warn(meta: &ScanMeta, results: &[ScanResult]) {
+    if let Err(e) = record_scan(meta, results) {
+        eprintln!("{} {}", "⚠️  Gagal menyimpan riwayat scan:".yellow(), e);
+    }
+}
+
+// Batas bawah tested_at; --days di luar rentang tanggal = semua riwayat
+fn cutoff(days: u32) -> String {
+    chrono::Duration::try_days(days as i64)
+        .and_then(|age| Local::now().checked_sub_signed(age))
+        .map(|time| time.format(TIME_FORMAT).to_string())
+        .unwrap_or_default()
+}
+
+/// Host yang working untuk target dalam `days` hari terakhir
+pub fn alive_hosts(conn: &Connection, target: &str, days: u32) -> anyhow::Result<Vec<HostRecord>> {
+    // Kolom non-agregat diambil dari baris MAX(tested_at) (perilaku SQLite)
+    let mut stmt = conn.prepare(
+        "SELECT subdomain, ip, provider, MAX(tested_at), latency_ms, COUNT(*)
+         FROM results
+         WHERE target = ?1 AND is_working = 1 AND tested_at >= ?2
+         GROUP BY subdomain
+         ORDER BY MAX(tested_at) DESC, subdomain",
+    )?;
+
+    let rows = stmt.query_map(params![target, cutoff(days)], |row| {
+        Ok(HostRecord {
+            subdomain: row.get(0)?,
+            ip: row.get(1)?,
+            provider: row.get(2)?,
+            last_seen: row.get(3)?,
+            latency_ms: row.get(4)?,
+            detail: format!("{}x working", row.get::<_, i64>(5)?),
+        })
+    })?;
+
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+/// Host yang pernah working untuk target tapi hasil terakhirnya gagal
+pub fn stopped_hosts(conn: &Connection, target: &str) -> anyhow::Result<Vec<HostRecord>> {
+    let mut stmt = conn.prepare(
+        "WITH latest AS (
+             SELECT subdomain, MAX(id) AS id FROM results WHERE target = ?1 GROUP BY subdomain
+         ),
+         last_ok AS (
+             SELECT subdomain, MAX(tested_at) AS tested_at
+             FROM results WHERE target = ?1 AND is_working = 1 GROUP BY subdomain
+         )
+         SELECT r.subdomain, r.ip, r.provider, r.tested_at, r.error_msg, last_ok.tested_at
+         FROM latest
+         JOIN results r ON r.id = latest.id
+         JOIN last_ok ON last_ok.subdomain = r.subdomain
+         WHERE r.is_working = 0
+         ORDER BY last_ok.tested_at DESC, r.subdomain",
+    )?;
+
+    let rows = stmt.query_map(params![target], |row| {
+        let error: Option<String> = row.get(4)?;
+        let last_ok: String = row.get(5)?;
+        Ok(HostRecord {
+            subdomain: row.get(0)?,
+            ip: row.get(1)?,
+            provider: row.get(2)?,
+            last_seen: row.get(3)?,
+            latency_ms: None,
+            detail: format!(
+                "terakhir working {}{}",
+                last_ok,
+                error.map(|e| format!(" | {}", e)).unwrap_or_default()
+            ),
+        })
+    })?;
+
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+/// Scan terbaru (semua target kalau target kosong)
+pub fn recent_scans(conn: &Connection, target: &str, limit: usize) -> anyhow::Result<Vec<ScanRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT s.id, s.started_at, s.target, s.domain, s.source,
+                COUNT(r.id), COALESCE(SUM(r.is_working), 0)
+         FROM scans s
+         LEFT JOIN results r ON r.scan_id = s.id
+         WHERE ?1 = '' OR s.target = ?1
+         GROUP BY s.id
+         ORDER BY s.id DESC
+         LIMIT ?2",
+    )?;
+
+    let rows = stmt.query_map(params![target, limit as i64], |row| {
+        Ok(ScanRecord {
+            id: row.get(0)?,
+            started_at: row.get(1)?,
+            target: row.get(2)?,
+            domain: row.get(3)?,
+            source: row.get(4)?,
+            total: row.get(5)?,
+            working: row.get(6)?,
+        })
+    })?;
+
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+/// Hasil satu scan dari riwayat (detail server/body tidak disimpan)
+pub fn load_scan(conn: &Connection, scan_id: i64) -> anyhow::Result<(ScanMeta, Vec<ScanResult>)> {
+    let meta = conn
+        .query_row(
+            "SELECT tool_version, started_at, target, domain, source, timeout, resolver FROM scans WHERE id = ?1",
+            params![scan_id],
+            |row| {
+                Ok(ScanMeta {
+                    tool_version: row.get(0)?,
+                    timestamp: row.get(1)?,
+                    target: row.get(2)?,
+                    domain: row.get(3)?,
+                    source: row.get(4)?,
+                    timeout: row.get(5)?,
+                    resolver: row.get::<_, Option<String>>(6)?.unwrap_or_default(),
+                })
+            },
+        )
+        .optional()?
+        .ok_or_else(|| anyhow::anyhow!("Scan #{} tidak ada di riwayat", scan_id))?;
+
+    let mut stmt = conn.prepare(
+        "SELECT subdomain, ip, provider, is_working, status_code, latency_ms, error_msg
+         FROM results WHERE scan_id = ?1 ORDER BY id",
+    )?;
+    let rows = stmt.query_map(params![scan_id], |row| {
+        let ip: String = row.get(1)?;
+        let provider: Option<String> = row.get(2)?;
+        let provider = provider.and_then(|p| p.parse::<Provider>().ok());
+        let is_working: bool = row.get(3)?;
+        let status_code: Option<u16> = row.get(4)?;
+        let latency_ms: Option<u64> = row.get(5)?;
+        let error_msg: Option<String> = row.get(6)?;
+
+        Ok(ScanResult {
+            subdomain: row.get(0)?,
+            ip: ip.clone(),
+            port: None,
+            provider,
+            cname_chain: Vec::new(),
+            cname_provider: None,
+            wildcard: None,
+            is_working,
+            status_code,
+            server: None,
+            cf_ray: None,
+            body_size: None,
+            latency_ms,
+            error_msg: error_msg.clone(),
+            retryable: false,
+            ip_results: if ip.is_empty() {
+                Vec::new()
+            } else {
+                vec![IpResult {
+                    ip,
+                    provider,
+                    is_working,
+                    status_code,
+                    latency_ms,
+                    error_msg,
+                }]
+            },
+        })
+    })?;
+
+    Ok((meta, rows.collect::<Result<_, _>>()?))
+}
+
+fn print_hosts(title: &str, hosts: &[HostRecord], working: bool) {
+    outln!("\n{} {}\n", title.bold(), format!("({})", hosts.len()).bright_black());
+
+    if hosts.is_empty() {
+        outln!("{}", "Tidak ada data".yellow());
+        return;
+    }
+
+    for host in hosts {
+        let mark = if working { "✓".green() } else { "✗".red() };
+        let subdomain = if working { host.subdomain.green() } else { host.subdomain.red() };
+        out!(
+            "{} {} {} {}",
+            mark,
+            subdomain,
+            format!("({})", host.ip).bright_black(),
+            format!("[{}]", host.provider.as_deref().unwrap_or("Non-CDN")).cyan()
+        );
+        if let Some(latency) = host.latency_ms {
+            out!(" {}", format!("{} ms", latency).bright_black());
+        }
+        outln!();
+        outln!(
+            "   {} {} | {}",
+            "→".bright_black(),
+            host.last_seen.bright_black(),
+            host.detail.bright_black()
+        );
+    }
+}
+
+fn print_scans(scans: &[ScanRecord]) {
+    outln!("\n{} {}\n", "SCAN TERAKHIR".bold(), format!("({})", scans.len()).bright_black());
+
+    if scans.is_empty() {
+        outln!("{}", "Belum ada riwayat scan".yellow());
+        return;
+    }
+
+    for scan in scans {
+        outln!(
+            "{} {} {} → {} {} | {}/{} working",
+            format!("#{}", scan.id).cyan(),
+            scan.started_at.bright_black(),
+            scan.domain,
+            scan.target.cyan(),
+            format!("[{}]", scan.source).bright_black(),
+            scan.working.to_string().green(),
+            scan.total
+        );
+    }
+}
+
+/// Jalankan satu query riwayat dan tampilkan hasilnya
+pub fn run_query(query: HistoryQuery, target: &str, days: u32) -> anyhow::Result<()> {
+    let conn = open()?;
+
+    match query {
+        HistoryQuery::Alive => {
+            let hosts = alive_hosts(&conn, target, days)?;
+            print_hosts(&format!("WORKING UNTUK {} ({} HARI TERAKHIR)", target, days), &hosts, true);
+        }
+        HistoryQuery::Stopped => {
+            let hosts = stopped_hosts(&conn, target)?;
+            print_hosts(&format!("BERHENTI WORKING UNTUK {}", target), &hosts, false);
+        }
+        HistoryQuery::Scans => {
+            print_scans(&recent_scans(&conn, target, 20)?);
+        }
+    }
+
+    outln!("\n{} {}", "Database:".bright_black(), Config::history_path().display().to_string().bright_black());
+    Ok(())
+}
+
+pub fn history_menu(target: &str) -> anyhow::Result<()> {
+    loop {
+        ui::print_header("SCAN HISTORY");
+        outln!("\n{} {}", "Target:".bright_black(), target.cyan());
+        outln!("\n1. ✅ Host working (N hari terakhir)");
+        outln!("2. ⛔ Host yang berhenti working");
+        outln!("3. 🗂️  Scan terakhir");
+        outln!("4. ↩️  Kembali");
+
+        out!("\n{} ", "Pilih:".bold());
+        let choice = ui::read_line();
+
+        let result = match choice.trim() {
+            "1" => {
+                out!("Berapa hari terakhir? [7]: ");
+                let days = ui::read_line().parse().unwrap_or(7);
+                run_query(HistoryQuery::Alive, target, days)
+            }
+            "2" => run_query(HistoryQuery::Stopped, target, 0),
+            "3" => run_query(HistoryQuery::Scans, target, 0),
+            "4" => return Ok(()),
+            _ => {
+                outln!("\n{}", "❌ Pilihan tidak valid".red());
+                Ok(())
+            }
+        };
+
+        if let Err(e) = result {
+            outln!("\n{} {}", "✗ Gagal membaca riwayat:".red(), e);
+        }
+        ui::pause();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cutoff_falls_back_to_all_history() {
+        assert_eq!(cutoff(u32::MAX), "");
+        assert!(cutoff(7) < Local::now().format(TIME_FORMAT).to_string());
+    }
+}
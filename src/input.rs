@@ -0,0 +1,91 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::Read;
+use std::net::Ipv6Addr;
+
+/// Baca daftar host dari file, atau stdin kalau source = "-"
+pub fn read_hosts(source: &str) -> anyhow::Result<Vec<String>> {
+    let content = if source == "-" {
+        let mut content = String::new();
+        std::io::stdin().read_to_string(&mut content)?;
+        content
+    } else {
+        fs::read_to_string(source)
+            .map_err(|e| anyhow::anyhow!("Gagal membaca {}: {}", source, e))?
+    };
+
+    Ok(parse_hosts(&content))
+}
+
+/// Normalisasi per baris + dedup, urutan asli dipertahankan
+pub fn parse_hosts(content: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    content
+        .lines()
+        .filter_map(normalize_host)
+        .filter(|host| seen.insert(host.clone()))
+        .collect()
+}
+
+/// `https://user@Sub.Example.com:8443/path?q` → `sub.example.com`
+/// Komentar (#), baris kosong dan entry tidak valid → None
+pub fn normalize_host(line: &str) -> Option<String> {
+    let line = line.split('#').next()?.trim();
+    // Output tool lain kadang "host ip" / "host,extra"
+    let mut entry = line.split(|c: char| c.is_whitespace() || c == ',').next()?;
+
+    if let Some((_, rest)) = entry.split_once("://") {
+        entry = rest;
+    }
+    entry = entry.split(['/', '?']).next()?;
+    if let Some((_, host)) = entry.rsplit_once('@') {
+        entry = host;
+    }
+
+    let host = if let Some(rest) = entry.strip_prefix('[') {
+        // [IPv6]:port
+        rest.split(']').next()?
+    } else if entry.parse::<Ipv6Addr>().is_ok() {
+        entry
+    } else {
+        entry.split(':').next()?
+    };
+
+    let host = host.trim_start_matches("*.").trim_end_matches('.').to_lowercase();
+    let valid = !host.is_empty()
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | ':'));
+
+    valid.then_some(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_host_strips_url_parts() {
+        assert_eq!(normalize_host("https://user:pw@Sub.Example.com:8443/path?q=1"), Some("sub.example.com".into()));
+        assert_eq!(normalize_host("example.com/login"), Some("example.com".into()));
+        assert_eq!(normalize_host("example.com?x=1"), Some("example.com".into()));
+        assert_eq!(normalize_host("WWW.Example.COM."), Some("www.example.com".into()));
+    }
+
+    #[test]
+    fn normalize_host_handles_ipv6_and_wildcards() {
+        assert_eq!(normalize_host("[2606:4700::1]:443"), Some("2606:4700::1".into()));
+        assert_eq!(normalize_host("http://[::1]/x"), Some("::1".into()));
+        assert_eq!(normalize_host("2606:4700::1"), Some("2606:4700::1".into()));
+        assert_eq!(normalize_host("*.cdn.example.com"), Some("cdn.example.com".into()));
+    }
+
+    #[test]
+    fn normalize_host_takes_first_field_and_skips_junk() {
+        assert_eq!(normalize_host("api.example.com 104.16.1.1"), Some("api.example.com".into()));
+        assert_eq!(normalize_host("api.example.com,extra"), Some("api.example.com".into()));
+        assert_eq!(normalize_host("  # komentar"), None);
+        assert_eq!(normalize_host(""), None);
+        assert_eq!(normalize_host("bad$host.com"), None);
+    }
+}
@@ -4,51 +4,42 @@ mod dns;
 mod ui;
 mod crtsh;
 mod results;
+mod probe;
+mod cdn;
+mod cleanip;
+mod wordlist;
+mod history;
+mod diff;
+mod input;
+mod cli;
+mod matrix;
+mod dnscompare;
+mod wildcard;
 
-use clap::Parser;
 use colored::Colorize;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use std::sync::Mutex;
 
-#[derive(Parser, Debug)]
-#[command(name = "InjectTools")]
-#[command(author = "hoshiyomi_id <t.me/hoshiyomi_id>")]
-#[command(version = "2.4.0")]
-#[command(about = "Bug Inject Scanner for Cloudflare Subdomains", long_about = None)]
-struct Args {
-    /// Target host (tunnel/proxy domain)
-    #[arg(short, long)]
-    target: Option<String>,
-
-    /// Domain to scan
-    #[arg(short, long)]
-    domain: Option<String>,
-
-    /// Test single subdomain
-    #[arg(short, long)]
-    subdomain: Option<String>,
-
-    /// Fetch subdomains from crt.sh
-    #[arg(long)]
-    crtsh: bool,
-
-    /// Timeout in seconds
-    #[arg(long, default_value = "10")]
-    timeout: u64,
-
-    /// Non-interactive mode
-    #[arg(long)]
-    non_interactive: bool,
-
-    /// View exported results
-    #[arg(long)]
-    view_results: bool,
-}
+use cli::{
+    BatchArgs, Cli, Command, ConfigCommand, DiscoverSource, RangesCommand, ResultsCommand,
+    TargetCommand, WordlistCommand,
+};
+
+// Exit code non-interactive (1 = error umum / argumen salah)
+const EXIT_WORKING: i32 = 0;
+const EXIT_NONE_WORKING: i32 = 3;
+const EXIT_TARGET_OFFLINE: i32 = 4;
+const EXIT_NETWORK_ERROR: i32 = 5;
+
+// Default clean IP scan dari menu interaktif
+const DEFAULT_SAMPLE: usize = 10;
+const DEFAULT_ATTEMPTS: u32 = 3;
 
 // Cache untuk target status
 struct TargetStatus {
+    target: String,
     is_online: bool,
     last_check: Instant,
 }
@@ -56,19 +47,32 @@ struct TargetStatus {
 impl TargetStatus {
     fn new() -> Self {
         Self {
+            target: String::new(),
             is_online: false,
             last_check: Instant::now() - Duration::from_secs(60), // Force first check
         }
     }
     
-    fn should_refresh(&self) -> bool {
-        self.last_check.elapsed() > Duration::from_secs(30)
+    // Ganti profile/target = cek ulang
+    fn should_refresh(&self, target: &str) -> bool {
+        self.target != target || self.last_check.elapsed() > Duration::from_secs(30)
     }
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let args = Args::parse();
+    let cli = Cli::parse_args();
+
+    if cli.json {
+        ui::set_output_mode(ui::OutputMode::Json);
+    } else if cli.quiet {
+        ui::set_output_mode(ui::OutputMode::Quiet);
+    }
+
+    if let Err(e) = config::init_paths(cli.config.as_deref()) {
+        eprintln!("{} {}", "✗ Error:".red(), e);
+        std::process::exit(1);
+    }
 
     // Setup signal handler
     let running = Arc::new(AtomicBool::new(true));
@@ -80,94 +84,306 @@ async fn main() -> anyhow::Result<()> {
         r.store(false, Ordering::SeqCst);
     })?;
 
-    // View results mode
-    if args.view_results {
-        results::view_results()?;
-        return Ok(());
+    let config = match load_config(cli.profile.as_deref(), &cli.resolvers) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{} {}", "✗ Error:".red(), e);
+            std::process::exit(1);
+        }
+    };
+
+    // Tanpa subcommand = menu interaktif
+    let Some(command) = cli.command else {
+        return interactive(&cli, config, running).await;
+    };
+
+    match run_command(command, config, running).await {
+        Ok(code) => std::process::exit(code),
+        Err(e) => {
+            eprintln!("{} {}", "✗ Error:".red(), e);
+            std::process::exit(1);
+        }
     }
+}
 
-    // Load or create config
+// Load config + pilih profile dari --profile
+fn load_config(profile: Option<&str>, resolvers: &[String]) -> anyhow::Result<config::Config> {
     let mut config = config::Config::load_or_create()?;
+    if let Some(name) = profile {
+        config.override_profile(name)?;
+    }
+    // --resolver menang atas scan.resolvers
+    let resolvers = if resolvers.is_empty() { &config.scan.resolvers } else { resolvers };
+    dns::Resolver::init(resolvers)?;
+    Ok(config)
+}
 
-    // Non-interactive mode
-    if args.non_interactive {
-        if let Some(target) = args.target {
-            config.target_host = target;
-            config.save()?;
-
-            if args.crtsh {
-                if let Some(domain) = args.domain {
-                    // Fetch from crt.sh and test
-                    ui::print_header("CRTSH SUBDOMAIN DISCOVERY");
-                    let subdomains = crtsh::fetch_subdomains(&domain).await?;
-                    println!("\n{} subdomains dari crt.sh\n", subdomains.len());
-                    
-                    let results = scanner::batch_test(
-                        &config.target_host,
-                        &subdomains,
-                        args.timeout,
-                        running.clone(),
-                    ).await?;
+// Jalankan subcommand, return exit code
+async fn run_command(
+    command: Command,
+    mut config: config::Config,
+    running: Arc<AtomicBool>,
+) -> anyhow::Result<i32> {
+    match command {
+        Command::Test { subdomain, target, probe } => {
+            let target = target.resolve(config.target())?;
+            let scan_options = probe.scan_options(&BatchArgs::default(), &config);
+            
+            let result = scanner::test_single(&target, &subdomain, &scan_options).await?;
+            results::emit(&result);
+            let meta = results::ScanMeta::new(&target, &subdomain, "single", scan_options.timeout);
+            history::record_or_warn(&meta, std::slice::from_ref(&result));
+            
+            Ok(scan_exit_code(std::slice::from_ref(&result), &target, &scan_options).await)
+        }
+        Command::Scan { input, retest, working_only, domain, target, probe, batch } => {
+            let target = target.resolve(config.target())?;
+            let scan_options = probe.scan_options(&batch, &config);
+            
+            let scan_results = match (retest, input) {
+                (Some(spec), _) => {
+                    retest_scan(&spec, &target, working_only, &scan_options, batch.format, running).await?
+                }
+                (None, Some(source)) => {
+                    let subdomains = input::read_hosts(&source)?;
+                    let label = if source == "-" { "stdin" } else { source.as_str() };
+                    outln!("\n{} {} hosts dari {}\n", "📥".cyan(), subdomains.len(), label);
+                    if subdomains.is_empty() {
+                        return Err(anyhow::anyhow!("Tidak ada host valid di input"));
+                    }
                     
-                    results::export_results(&results, &domain)?;
-                } else {
-                    eprintln!("{}", "Error: --domain required untuk --crtsh".red());
-                    std::process::exit(1);
+                    let domain = domain.unwrap_or_else(|| "input".to_string());
+                    let source = format!("input:{}", label);
+                    test_and_save(&target, &domain, &source, &subdomains, &scan_options, batch.format, running).await?
                 }
-            } else if let Some(subdomain) = args.subdomain {
-                // Single test
-                scanner::test_single(&config.target_host, &subdomain, args.timeout).await?;
+                (None, None) => return Err(anyhow::anyhow!("--input atau --retest required")),
+            };
+            
+            Ok(scan_exit_code(&scan_results, &target, &scan_options).await)
+        }
+        Command::Matrix { input, targets, profiles, all_profiles, domain, probe, batch } => {
+            let scan_options = probe.scan_options(&batch, &config);
+            let profiles = if all_profiles { config.profiles.keys().cloned().collect() } else { profiles };
+            let targets = matrix_targets(&config, &targets, &profiles, &probe, &batch)?;
+            
+            let subdomains = input::read_hosts(&input)?;
+            let label = if input == "-" { "stdin" } else { input.as_str() };
+            outln!("\n{} {} hosts dari {}", "📥".cyan(), subdomains.len(), label);
+            if subdomains.is_empty() {
+                return Err(anyhow::anyhow!("Tidak ada host valid di input"));
+            }
+            
+            ui::print_header("MATRIX SCAN");
+            let grid = matrix::run(&targets, &subdomains, &scan_options, running).await;
+            
+            let domain = domain.unwrap_or_else(|| "input".to_string());
+            let source = format!("matrix:{}", label);
+            let labels: Vec<&str> = targets.iter().map(|t| t.label.as_str()).collect();
+            let meta = results::ScanMeta::new(&labels.join(","), &domain, &source, scan_options.timeout);
+            let report = matrix::MatrixReport::new(meta, &targets, &grid);
+            matrix::print_table(&report);
+            
+            // Riwayat dicatat per target supaya query alive/stopped tetap jalan
+            for (idx, target) in targets.iter().enumerate() {
+                let meta = results::ScanMeta::new(&target.host, &domain, &source, target.options.timeout);
+                history::record_or_warn(&meta, &matrix::column(&grid, idx));
+            }
+            matrix::export(&report, batch.format)?;
+            
+            Ok(if report.any_working() {
+                EXIT_WORKING
+            } else if report.all_dns_failed() {
+                EXIT_NETWORK_ERROR
             } else {
-                eprintln!("{}", "Error: --subdomain atau --crtsh required".red());
-                std::process::exit(1);
+                EXIT_NONE_WORKING
+            })
+        }
+        Command::DnsCompare { input, resolvers, probe_answers, domain, target, probe, batch } => {
+            let scan_options = probe.scan_options(&batch, &config);
+            let resolvers = dnscompare::resolvers(&resolvers)?;
+            let target = if probe_answers { Some(target.resolve(config.target())?) } else { None };
+            
+            let subdomains = input::read_hosts(&input)?;
+            let label = if input == "-" { "stdin" } else { input.as_str() };
+            outln!("\n{} {} hosts dari {}", "📥".cyan(), subdomains.len(), label);
+            if subdomains.is_empty() {
+                return Err(anyhow::anyhow!("Tidak ada host valid di input"));
             }
             
-            return Ok(());
-        } else {
-            eprintln!("{}", "Error: --target required untuk non-interactive mode".red());
-            eprintln!("\n{}", "Usage:".cyan());
-            eprintln!("  injecttools -t host.com -s subdomain.com --non-interactive");
-            eprintln!("  injecttools -t host.com --crtsh -d domain.com --non-interactive");
-            std::process::exit(1);
+            ui::print_header("DNS COMPARISON");
+            let rows = dnscompare::run(&resolvers, &subdomains, target.as_deref(), &scan_options, running).await;
+            
+            let domain = domain.unwrap_or_else(|| "input".to_string());
+            let source = format!("dns-compare:{}", label);
+            let specs: Vec<String> = resolvers.iter().map(|r| r.spec.clone()).collect();
+            let mut meta =
+                results::ScanMeta::new(target.as_deref().unwrap_or("-"), &domain, &source, scan_options.timeout);
+            meta.resolver = specs.join(", ");
+            let report = dnscompare::CompareReport { meta, resolvers: specs, rows };
+            dnscompare::print_table(&report);
+            dnscompare::export(&report, batch.format)?;
+            
+            Ok(if report.all_failed() {
+                EXIT_NETWORK_ERROR
+            } else if target.is_some() && !report.any_working() {
+                EXIT_NONE_WORKING
+            } else {
+                EXIT_WORKING
+            })
+        }
+        Command::Discover { domain, source, wordlist, list_only, target, probe, batch } => {
+            let scan_options = probe.scan_options(&batch, &config);
+            
+            let (subdomains, source) = if source == DiscoverSource::Wordlist || wordlist.is_some() {
+                wordlist_candidates(&config, &domain, wordlist.as_deref(), &scan_options, running.clone()).await?
+            } else {
+                ui::print_header("CRTSH SUBDOMAIN DISCOVERY");
+                match crtsh::fetch_subdomains(&domain).await {
+                    Ok(subdomains) => {
+                        outln!("\n{} subdomains dari crt.sh\n", subdomains.len());
+                        (subdomains, "crtsh".to_string())
+                    }
+                    Err(e) => {
+                        eprintln!("{} {}", "✗ Gagal fetch dari crt.sh:".red(), e);
+                        return Ok(EXIT_NETWORK_ERROR);
+                    }
+                }
+            };
+            
+            // Daftar subdomain saja, untuk dipipe ke `scan --input -`
+            if list_only {
+                for subdomain in &subdomains {
+                    println!("{}", subdomain);
+                }
+                return Ok(if subdomains.is_empty() { EXIT_NONE_WORKING } else { EXIT_WORKING });
+            }
+            
+            let target = target.resolve(config.target())?;
+            let scan_results =
+                test_and_save(&target, &domain, &source, &subdomains, &scan_options, batch.format, running).await?;
+            Ok(scan_exit_code(&scan_results, &target, &scan_options).await)
+        }
+        Command::Cleanip { cidr, sample, sweep, attempts, target, probe, batch } => {
+            let target = target.resolve(config.target())?;
+            let scan_options = probe.scan_options(&batch, &config);
+            let cidrs = match &cidr {
+                Some(list) => cleanip::parse_cidrs(list)?,
+                None => cleanip::default_ranges(probe.ip_version),
+            };
+            let clean_options = cleanip::CleanIpOptions { cidrs, sample, sweep, attempts };
+            
+            ui::print_header("CLEAN IP SCANNER");
+            let clean = cleanip::scan(&target, &clean_options, &scan_options, running).await?;
+            
+            let scan_results = cleanip::to_scan_results(&clean, &scan_options);
+            scan_results.iter().for_each(results::emit);
+            // Tidak dicatat ke riwayat: subdomain = IP, akan mengotori history alive/stopped
+            let meta = results::ScanMeta::new(&target, "cleanip", "cleanip", scan_options.timeout);
+            results::export_results(&scan_results, &meta, batch.format)?;
+            
+            Ok(scan_exit_code(&scan_results, &target, &scan_options).await)
+        }
+        Command::Target { command: TargetCommand::Check { target, timeout } } => {
+            let target = cli::TargetArg { target }.resolve(config.target())?;
+            let timeout = timeout
+                .or(config.profile().and_then(|p| p.timeout))
+                .unwrap_or(config.scan.timeout);
+            let online = scanner::test_target(&target, timeout, &dns::Resolver::shared()).await?;
+            Ok(if online { EXIT_WORKING } else { EXIT_TARGET_OFFLINE })
+        }
+        Command::Results { command } => {
+            match command {
+                ResultsCommand::List => {
+                    results::print_exports(None)?;
+                }
+                ResultsCommand::Show { file } => {
+                    results::show_export(&results::find_export(file.as_deref())?)?;
+                }
+                ResultsCommand::Diff { old, new, json_out } => {
+                    diff::run(&old, &new, json_out.as_deref())?;
+                }
+            }
+            Ok(EXIT_WORKING)
+        }
+        Command::History { query, days, target } => {
+            let target = target.target.unwrap_or_else(|| config.target().to_string());
+            history::run_query(query, &target, days)?;
+            Ok(EXIT_WORKING)
+        }
+        Command::Config { command } => {
+            match command {
+                ConfigCommand::Get { key: Some(key) } => println!("{}", config.get(&key)?),
+                ConfigCommand::Get { key: None } => print!("{}", toml::to_string_pretty(&config)?),
+                ConfigCommand::Set { key, value } => {
+                    config.set(&key, &value)?;
+                    config.save()?;
+                    outln!("{} {} = {}", "✓".green(), key, config.get(&key)?);
+                }
+                ConfigCommand::Path => {
+                    println!("config: {}", config::Config::config_path().display());
+                    println!("home:   {}", config::Config::home_dir().display());
+                }
+            }
+            Ok(EXIT_WORKING)
+        }
+        Command::Wordlist { command } => {
+            match command {
+                None => wordlist::wordlist_menu(&mut config).await?,
+                Some(WordlistCommand::List) => wordlist::print_wordlists(&config),
+                Some(WordlistCommand::Use { file }) => wordlist::use_wordlist(&mut config, &file)?,
+            }
+            Ok(EXIT_WORKING)
+        }
+        Command::Ranges { command: RangesCommand::Update { source } } => {
+            cdn::update_ranges(&source).await?;
+            Ok(EXIT_WORKING)
         }
     }
+}
 
+// Menu interaktif (default tanpa subcommand)
+async fn interactive(cli: &Cli, mut config: config::Config, running: Arc<AtomicBool>) -> anyhow::Result<()> {
     // Target status cache
     let target_status = Arc::new(Mutex::new(TargetStatus::new()));
 
     // Interactive mode
     loop {
+        // Dihitung ulang tiap loop karena profile bisa diganti dari menu
+        let scan_options = cli.probe.scan_options(&cli.batch, &config);
+        let target = config.target().to_string();
+
         ui::clear_screen();
         ui::print_header("INJECTTOOLS v2.4.0");
         
         // Display target status dengan auto-check
-        if !config.target_host.is_empty() {
+        if !target.is_empty() {
             let mut status = target_status.lock().unwrap();
             
-            // Refresh status jika sudah > 30 detik
-            if status.should_refresh() {
-                print!("\n🔄 Checking target status...");
+            // Refresh status jika sudah > 30 detik atau target berubah
+            if status.should_refresh(&target) {
+                out!("\n🔄 Checking target status...");
                 std::io::Write::flush(&mut std::io::stdout()).ok();
                 
                 // Quick check target (async)
-                let target_clone = config.target_host.clone();
+                let target_clone = target.clone();
                 let is_online = tokio::task::block_in_place(|| {
                     tokio::runtime::Handle::current().block_on(async {
-                        check_target_quick(&target_clone, 5).await
+                        check_target_quick(&target_clone, scan_options.timeout, &scan_options.resolver).await
                     })
                 });
                 
+                status.target = target.clone();
                 status.is_online = is_online;
                 status.last_check = Instant::now();
                 
                 // Clear checking message
-                print!("\r\x1B[K");
+                out!("\r\x1B[K");
             }
             
             let status_text = if status.is_online {
-                format!("🟢 ONLINE")
+                "🟢 ONLINE".to_string()
             } else {
-                format!("🔴 OFFLINE")
+                "🔴 OFFLINE".to_string()
             };
             
             let status_color = if status.is_online {
@@ -176,119 +392,187 @@ async fn main() -> anyhow::Result<()> {
                 status_text.red()
             };
             
-            println!("\n{}", "─".repeat(50).bright_black());
-            println!("{} {}", "Target:".bright_black(), config.target_host.cyan().bold());
-            println!("{} {}", "Status:".bright_black(), status_color);
-            println!("{}", "─".repeat(50).bright_black());
+            outln!("\n{}", "─".repeat(50).bright_black());
+            if let Some(name) = config.profile_name() {
+                outln!("{} {}", "Profile:".bright_black(), name.magenta().bold());
+            }
+            outln!("{} {}", "Target:".bright_black(), target.cyan().bold());
+            outln!("{} {}", "Status:".bright_black(), status_color);
+            outln!("{}", "─".repeat(50).bright_black());
         }
         
-        println!("\n{}", "MAIN MENU".bold());
-        println!("{}" , "━".repeat(50).cyan());
-        println!("\n1. 🔍 Test Single Subdomain");
-        println!("2. 🌐 Fetch & Test dari crt.sh");
-        println!("3. 📊 View Exported Results");
-        println!("4. ⚙️  Change Target Host");
-        println!("5. 🚪 Exit");
-        println!("\n{}", "━".repeat(50).cyan());
+        outln!("\n{}", "MAIN MENU".bold());
+        outln!("{}" , "━".repeat(50).cyan());
+        outln!("\n1. 🔍 Test Single Subdomain");
+        outln!("2. 🌐 Fetch & Test dari crt.sh");
+        outln!("3. 📖 Brute-force Subdomain (Wordlist)");
+        outln!("4. 🧹 Clean IP Scanner");
+        outln!("5. 📊 View Exported Results");
+        outln!("6. 🔀 Diff Scan Results");
+        outln!("7. 🗂️  Scan History");
+        outln!("8. 📚 Wordlist Manager");
+        outln!("9. ⚙️  Target & Profiles");
+        outln!("10. 🛠️  Scan Settings");
+        outln!("11. 🚪 Exit");
+        outln!("\n{}", "━".repeat(50).cyan());
         
-        print!("\n{} ", "Pilih:".bold());
+        out!("\n{} ", "Pilih:".bold());
         let choice = ui::read_line();
 
         match choice.trim() {
             "1" => {
-                if config.target_host.is_empty() {
-                    println!("\n{}", "⚠️  Set target host dulu (pilih menu 4)!".yellow());
+                if target.is_empty() {
+                    outln!("\n{}", "⚠️  Set target host dulu (pilih menu 9)!".yellow());
                     ui::pause();
                     continue;
                 }
                 
                 ui::print_header("TEST SINGLE SUBDOMAIN");
-                print!("\nMasukkan subdomain: ");
+                out!("\nMasukkan subdomain: ");
                 let subdomain = ui::read_line();
                 if !subdomain.is_empty() {
-                    scanner::test_single(&config.target_host, &subdomain, args.timeout).await?;
+                    let result = scanner::test_single(&target, &subdomain, &scan_options).await?;
+                    let meta = results::ScanMeta::new(&target, &subdomain, "single", scan_options.timeout);
+                    history::record_or_warn(&meta, &[result]);
                 }
                 ui::pause();
             }
             "2" => {
-                if config.target_host.is_empty() {
-                    println!("\n{}", "⚠️  Set target host dulu (pilih menu 4)!".yellow());
+                if target.is_empty() {
+                    outln!("\n{}", "⚠️  Set target host dulu (pilih menu 9)!".yellow());
                     ui::pause();
                     continue;
                 }
                 
                 ui::print_header("CRTSH SUBDOMAIN DISCOVERY");
-                print!("\nMasukkan domain (contoh: cloudflare.com): ");
+                out!("\nMasukkan domain (contoh: cloudflare.com): ");
                 let domain = ui::read_line();
                 if !domain.is_empty() {
-                    println!("\n{}", "📡 Fetching subdomains dari crt.sh...".cyan());
+                    outln!("\n{}", "📡 Fetching subdomains dari crt.sh...".cyan());
                     match crtsh::fetch_subdomains(&domain).await {
                         Ok(subdomains) => {
-                            println!("{} {} subdomains ditemukan\n", "✓".green(), subdomains.len());
+                            outln!("{} {} subdomains ditemukan\n", "✓".green(), subdomains.len());
                             
                             if subdomains.is_empty() {
-                                println!("{}", "Tidak ada subdomain ditemukan".yellow());
+                                outln!("{}", "Tidak ada subdomain ditemukan".yellow());
                             } else {
-                                println!("{}", "Mulai testing...".cyan());
+                                outln!("{}", "Mulai testing...".cyan());
                                 let results = scanner::batch_test(
-                                    &config.target_host,
+                                    &target,
                                     &subdomains,
-                                    args.timeout,
+                                    &scan_options,
                                     running.clone(),
                                 ).await?;
                                 
-                                results::export_results(&results, &domain)?;
+                                let meta = results::ScanMeta::new(&target, &domain, "crtsh", scan_options.timeout);
+                                save_results(&results, &meta, cli.batch.format)?;
                             }
                         }
                         Err(e) => {
-                            println!("{} {}", "✗".red(), format!("Gagal fetch dari crt.sh: {}", e).red());
+                            outln!("{} {}", "✗".red(), format!("Gagal fetch dari crt.sh: {}", e).red());
                         }
                     }
                 }
                 ui::pause();
             }
             "3" => {
-                results::view_results()?;
+                if target.is_empty() {
+                    outln!("\n{}", "⚠️  Set target host dulu (pilih menu 9)!".yellow());
+                    ui::pause();
+                    continue;
+                }
+                
+                ui::print_header("WORDLIST BRUTE-FORCE");
+                out!("\nMasukkan domain (contoh: cloudflare.com): ");
+                let domain = ui::read_line();
+                if !domain.is_empty() {
+                    let scan = async {
+                        let (subdomains, source) =
+                            wordlist_candidates(&config, &domain, None, &scan_options, running.clone()).await?;
+                        test_and_save(&target, &domain, &source, &subdomains, &scan_options, cli.batch.format, running.clone()).await
+                    };
+                    if let Err(e) = scan.await {
+                        outln!("{} {}", "✗".red(), format!("Brute-force gagal: {}", e).red());
+                    }
+                }
                 ui::pause();
             }
             "4" => {
-                ui::print_header("CHANGE TARGET HOST");
-                
-                if !config.target_host.is_empty() {
-                    println!("\n{} {}", "Current target:".bright_black(), config.target_host.cyan());
+                if target.is_empty() {
+                    outln!("\n{}", "⚠️  Set target host dulu (pilih menu 9)!".yellow());
+                    ui::pause();
+                    continue;
                 }
                 
-                print!("\nMasukkan target host baru: ");
-                let target = ui::read_line();
-                
-                if !target.is_empty() {
-                    // Test target connection
-                    println!("\n{}", "🔍 Testing target connection...".cyan());
-                    scanner::test_target(&target, args.timeout).await?;
-                    
-                    // Save if test successful
-                    config.target_host = target;
-                    config.save()?;
-                    
-                    println!("\n{}", "✓ Target host updated".green());
-                    
-                    // Force refresh status
-                    let mut status = target_status.lock().unwrap();
-                    status.last_check = Instant::now() - Duration::from_secs(60);
-                    
-                    std::thread::sleep(std::time::Duration::from_secs(2));
+                ui::print_header("CLEAN IP SCANNER");
+                out!("\nMasukkan CIDR (pisah koma, kosong = range Cloudflare): ");
+                let input = ui::read_line();
+                let cidrs = if input.is_empty() {
+                    Ok(cleanip::default_ranges(cli.probe.ip_version))
                 } else {
-                    println!("\n{}", "⚠️  Target host tidak boleh kosong".yellow());
-                }
+                    cleanip::parse_cidrs(&input)
+                };
                 
+                match cidrs {
+                    Ok(cidrs) => {
+                        out!("Jumlah IP acak per CIDR [{}]: ", DEFAULT_SAMPLE);
+                        let sample = ui::read_line()
+                            .parse()
+                            .ok()
+                            .filter(|n| (1..=cleanip::MAX_SAMPLE_PER_CIDR).contains(n))
+                            .unwrap_or(DEFAULT_SAMPLE);
+                        let clean_options = cleanip::CleanIpOptions {
+                            cidrs,
+                            sample,
+                            sweep: false,
+                            attempts: DEFAULT_ATTEMPTS,
+                        };
+                        
+                        let clean = cleanip::scan(
+                            &target,
+                            &clean_options,
+                            &scan_options,
+                            running.clone(),
+                        ).await?;
+                        
+                        let meta = results::ScanMeta::new(&target, "cleanip", "cleanip", scan_options.timeout);
+                        results::export_results(&cleanip::to_scan_results(&clean, &scan_options), &meta, cli.batch.format)?;
+                    }
+                    Err(e) => {
+                        outln!("\n{} {}", "✗".red(), e.to_string().red());
+                    }
+                }
                 ui::pause();
             }
             "5" => {
-                println!("\n{}", "👋 Terima kasih telah menggunakan InjectTools!".green());
+                results::view_results()?;
+                ui::pause();
+            }
+            "6" => {
+                if let Err(e) = diff::diff_menu() {
+                    outln!("\n{} {}", "✗ Diff gagal:".red(), e);
+                }
+                ui::pause();
+            }
+            "7" => {
+                history::history_menu(&target)?;
+            }
+            "8" => {
+                wordlist::wordlist_menu(&mut config).await?;
+            }
+            "9" => {
+                target_menu(&mut config, scan_options.timeout).await?;
+                ui::pause();
+            }
+            "10" => {
+                settings_menu(&mut config)?;
+            }
+            "11" => {
+                outln!("\n{}", "👋 Terima kasih telah menggunakan InjectTools!".green());
                 break;
             }
             _ => {
-                println!("\n{}", "❌ Pilihan tidak valid".red());
+                outln!("\n{}", "❌ Pilihan tidak valid".red());
                 ui::pause();
             }
         }
@@ -297,76 +581,375 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-// Quick check target status menggunakan curl --resolve
-// curl -s --max-time $TIMEOUT --resolve $TARGET:443:$IP https://$TARGET/ -o /dev/null
-async fn check_target_quick(target: &str, timeout: u64) -> bool {
-    // Step 1: Resolve target domain untuk dapat IP
-    let ip = match dns::resolve_domain_first(target).await {
-        Ok(ip) => ip,
-        Err(_) => return false,
-    };
-    
-    // Step 2: Test HTTPS connection dengan curl --resolve
-    // curl -s --max-time 5 --resolve target:443:ip https://target/ -o /dev/null
-    let resolve_arg = format!("{}:443:{}", target, ip);
-    let url = format!("https://{}/", target);
-    
-    let output = tokio::process::Command::new("curl")
-        .arg("-s")                          // Silent mode
-        .arg("--max-time")
-        .arg(timeout.to_string())           // Timeout
-        .arg("--resolve")
-        .arg(&resolve_arg)                  // Resolve target:443 to IP
-        .arg("-k")                          // Allow insecure SSL
-        .arg(&url)                          // URL to test
-        .arg("-o")
-        .arg("/dev/null")                   // Discard output
-        .output()
-        .await;
+// Edit [scan] di config.toml, berlaku untuk scan berikutnya
+fn settings_menu(config: &mut config::Config) -> anyhow::Result<()> {
+    const FIELDS: [(&str, &str); 7] = [
+        ("scan.timeout", "Timeout (detik)"),
+        ("scan.concurrency", "Concurrency"),
+        ("scan.delay_ms", "Delay per host (ms)"),
+        ("scan.retries", "Retries"),
+        ("scan.ports", "Ports (pisah koma)"),
+        ("scan.mode", "Probe mode (tls/http/ws)"),
+        ("scan.resolvers", "DNS resolver (restart)"),
+    ];
     
-    if let Ok(result) = output {
-        // Exit code 0 = success (connection successful)
-        if result.status.success() {
-            return true;
+    loop {
+        ui::clear_screen();
+        ui::print_header("SCAN SETTINGS");
+        
+        outln!();
+        for (idx, (key, label)) in FIELDS.iter().enumerate() {
+            outln!("{}. {:<26} {}", (idx + 1).to_string().cyan(), label, config.get(key)?.yellow());
+        }
+        outln!("\n{}", "Flag CLI (--timeout, --port, ...) tetap menang atas nilai ini".bright_black());
+        
+        out!("\n{} ", "Pilih nomor (Enter = kembali):".bold());
+        let choice = ui::read_line();
+        if choice.is_empty() {
+            return Ok(());
+        }
+        
+        let Some((key, label)) = choice.parse::<usize>().ok().and_then(|n| n.checked_sub(1)).and_then(|i| FIELDS.get(i)) else {
+            outln!("\n{}", "❌ Pilihan tidak valid".red());
+            ui::pause();
+            continue;
+        };
+        
+        out!("{} [{}]: ", label, config.get(key)?);
+        let value = ui::read_line();
+        if value.is_empty() {
+            continue;
         }
         
-        // Exit codes yang dianggap "target reachable":
-        // - 0: Success
-        // - 22: HTTP error (tapi connection berhasil)
-        if let Some(code) = result.status.code() {
-            if code == 0 || code == 22 {
-                return true;
+        match config.set(key, &value) {
+            Ok(()) => {
+                config.save()?;
+                outln!("\n{} {} = {}", "✓".green(), key, config.get(key)?);
             }
+            Err(e) => outln!("\n{} {}", "✗".red(), e.to_string().red()),
         }
+        ui::pause();
     }
+}
+
+// Pilih / tambah profile target, atau set target tanpa profile
+async fn target_menu(config: &mut config::Config, timeout: u64) -> anyhow::Result<()> {
+    ui::print_header("TARGET & PROFILES");
     
-    // Fallback: Try HTTP port 80
-    let resolve_arg_80 = format!("{}:80:{}", target, ip);
-    let url_80 = format!("http://{}/", target);
+    let active = config.profile_name().map(str::to_string);
+    let names: Vec<String> = config.profiles.keys().cloned().collect();
     
-    let output_80 = tokio::process::Command::new("curl")
-        .arg("-s")
-        .arg("--max-time")
-        .arg("3")
-        .arg("--resolve")
-        .arg(&resolve_arg_80)
-        .arg(&url_80)
-        .arg("-o")
-        .arg("/dev/null")
-        .output()
-        .await;
+    outln!();
+    let mark = |on: bool| if on { "★".yellow().to_string() } else { " ".to_string() };
+    outln!(
+        "{} {}. {} {}",
+        mark(active.is_none()),
+        "0".cyan(),
+        "(tanpa profile)".bright_black(),
+        if config.target_host.is_empty() { "-" } else { config.target_host.as_str() }
+    );
+    for (idx, name) in names.iter().enumerate() {
+        let profile = &config.profiles[name];
+        let mut extra = Vec::new();
+        if let Some(port) = profile.port {
+            extra.push(format!("port {}", port));
+        }
+        if let Some(ws_path) = &profile.ws_path {
+            extra.push(format!("ws {}", ws_path));
+        } else if let Some(path) = &profile.path {
+            extra.push(format!("path {}", path));
+        }
+        if let Some(sni) = &profile.sni {
+            extra.push(format!("sni {}", sni));
+        }
+        outln!(
+            "{} {}. {} → {} {}",
+            mark(active.as_deref() == Some(name.as_str())),
+            (idx + 1).to_string().cyan(),
+            name.bold(),
+            profile.target_host.cyan(),
+            if extra.is_empty() { String::new() } else { format!("({})", extra.join(", ")).bright_black().to_string() }
+        );
+    }
     
-    if let Ok(result) = output_80 {
-        if result.status.success() {
-            return true;
+    outln!("\n{}", "Nomor = aktifkan, t = ganti target tanpa profile, a = tambah profile".bright_black());
+    out!("\n{} ", "Pilih:".bold());
+    let choice = ui::read_line();
+    
+    match choice.as_str() {
+        "" => {}
+        "t" => {
+            out!("\nMasukkan target host baru: ");
+            let target = ui::read_line();
+            if target.is_empty() {
+                outln!("\n{}", "⚠️  Target host tidak boleh kosong".yellow());
+                return Ok(());
+            }
+            
+            // Test target connection
+            outln!("\n{}", "🔍 Testing target connection...".cyan());
+            scanner::test_target(&target, timeout, &dns::Resolver::shared()).await?;
+            
+            config.target_host = target;
+            config.use_profile(None)?;
+            config.save()?;
+            outln!("\n{}", "✓ Target host updated".green());
         }
-        
-        if let Some(code) = result.status.code() {
-            if code == 0 || code == 22 {
-                return true;
+        "a" => {
+            let profile = prompt_profile()?;
+            let Some((name, profile)) = profile else {
+                outln!("\n{}", "⚠️  Nama profile dan target host wajib diisi".yellow());
+                return Ok(());
+            };
+            
+            outln!("\n{}", "🔍 Testing target connection...".cyan());
+            scanner::test_target(&profile.target_host, profile.timeout.unwrap_or(timeout), &dns::Resolver::shared()).await?;
+            
+            config.profiles.insert(name.clone(), profile);
+            config.use_profile(Some(&name))?;
+            config.save()?;
+            outln!("\n{} {}", "✓ Profile disimpan & aktif:".green(), name);
+        }
+        _ => match choice.parse::<usize>() {
+            Ok(0) => {
+                config.use_profile(None)?;
+                config.save()?;
+                outln!("\n{}", "✓ Tanpa profile".green());
             }
+            Ok(n) if n <= names.len() => {
+                config.use_profile(Some(&names[n - 1]))?;
+                config.save()?;
+                outln!("\n{} {}", "✓ Profile aktif:".green(), names[n - 1]);
+            }
+            _ => outln!("\n{}", "❌ Pilihan tidak valid".red()),
+        },
+    }
+    
+    Ok(())
+}
+
+// Input profile baru dari menu, field kosong = tidak di-override
+fn prompt_profile() -> anyhow::Result<Option<(String, config::Profile)>> {
+    let optional = |label: &str| -> Option<String> {
+        out!("{} (kosong = default): ", label);
+        Some(ui::read_line()).filter(|v| !v.is_empty())
+    };
+    
+    out!("\nNama profile: ");
+    let name = ui::read_line();
+    out!("Target host: ");
+    let target_host = ui::read_line();
+    if name.is_empty() || target_host.is_empty() {
+        return Ok(None);
+    }
+    
+    let sni = optional("SNI");
+    let host_header = optional("Host header");
+    let port = optional("Port").map(|v| config::parse_positive::<u16>("Port", &v)).transpose()?;
+    let ws_path = optional("WebSocket path");
+    let path = if ws_path.is_none() { optional("HTTP path") } else { None };
+    let timeout = optional("Timeout (detik)").map(|v| config::parse_positive::<u64>("Timeout", &v)).transpose()?;
+    
+    Ok(Some((name, config::Profile { target_host, sni, host_header, port, path, ws_path, timeout })))
+}
+
+// Exit code akhir scan untuk scripting: ada yang working, target offline,
+// semua DNS gagal (network), atau tidak ada yang working
+async fn scan_exit_code(scan_results: &[scanner::ScanResult], target: &str, options: &scanner::ScanOptions) -> i32 {
+    if scan_results.iter().any(|r| r.is_working) {
+        return EXIT_WORKING;
+    }
+    if !scan_results.is_empty() && scan_results.iter().all(|r| r.ip_results.is_empty()) {
+        return EXIT_NETWORK_ERROR;
+    }
+    if !check_target_quick(target, options.timeout, &options.resolver).await {
+        return EXIT_TARGET_OFFLINE;
+    }
+    EXIT_NONE_WORKING
+}
+
+// Export hasil batch dan simpan ke riwayat scan
+fn save_results(
+    scan_results: &[scanner::ScanResult],
+    meta: &results::ScanMeta,
+    format: results::ExportFormat,
+) -> anyhow::Result<()> {
+    history::record_or_warn(meta, scan_results);
+    results::export_results(scan_results, meta, format)
+}
+
+// Test ulang subdomain dari export/riwayat lama ke target (bisa target lain)
+async fn retest_scan(
+    spec: &str,
+    target: &str,
+    working_only: bool,
+    scan_options: &scanner::ScanOptions,
+    format: results::ExportFormat,
+    running: Arc<AtomicBool>,
+) -> anyhow::Result<Vec<scanner::ScanResult>> {
+    let previous = results::ScanSet::load(spec)?;
+    let mut seen = std::collections::HashSet::new();
+    let subdomains: Vec<String> = previous
+        .results
+        .iter()
+        .filter(|r| !working_only || r.is_working)
+        .filter(|r| seen.insert(r.subdomain.as_str()))
+        .map(|r| r.subdomain.clone())
+        .collect();
+
+    ui::print_header("RE-TEST PREVIOUS RESULTS");
+    outln!("\n{} {}", "Sumber:".bright_black(), previous.label.cyan());
+    if let Some(meta) = &previous.meta {
+        outln!("{} {} ({})", "Target lama:".bright_black(), meta.target, meta.timestamp.bright_black());
+    }
+    outln!("{} {}", "Target:".bright_black(), target.cyan());
+    outln!(
+        "{} {} subdomains{}",
+        "Total:".bright_black(),
+        subdomains.len().to_string().yellow(),
+        if working_only { " (working saja)" } else { "" }
+    );
+
+    // Txt lama tidak memuat host gagal; --working-only tetap lengkap
+    if previous.partial && !working_only {
+        eprintln!(
+            "{} {} hanya berisi host working / non-Cloudflare, host gagal tidak ikut dites. \
+             Pakai export json/csv/ndjson atau #id riwayat untuk re-test lengkap",
+            "⚠️  Sumber tidak lengkap:".yellow(),
+            previous.label
+        );
+    }
+
+    if subdomains.is_empty() {
+        outln!("\n{}", "Tidak ada subdomain untuk dites".yellow());
+        return Ok(Vec::new());
+    }
+
+    let domain = previous
+        .meta
+        .as_ref()
+        .map(|m| m.domain.clone())
+        .filter(|d| !d.is_empty())
+        .unwrap_or_else(|| "retest".to_string());
+
+    let scan_results = scanner::batch_test(target, &subdomains, scan_options, running).await?;
+    let meta = results::ScanMeta::new(
+        target,
+        &domain,
+        &format!("retest:{}", previous.label),
+        scan_options.timeout,
+    );
+    save_results(&scan_results, &meta, format)?;
+    Ok(scan_results)
+}
+
+// Kolom matrix dari -t dan --profiles, tanpa duplikat
+fn matrix_targets(
+    config: &config::Config,
+    hosts: &[String],
+    profiles: &[String],
+    probe: &cli::ProbeArgs,
+    batch: &BatchArgs,
+) -> anyhow::Result<Vec<matrix::MatrixTarget>> {
+    let mut targets: Vec<matrix::MatrixTarget> = Vec::new();
+    
+    for name in profiles {
+        let profile = config
+            .profiles
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("Profile tidak ditemukan: {}", name))?;
+        targets.push(matrix::MatrixTarget {
+            label: name.clone(),
+            host: profile.target_host.clone(),
+            options: probe.scan_options_for(batch, config, Some(profile)),
+        });
+    }
+    for host in hosts.iter().filter(|h| !h.is_empty()) {
+        if targets.iter().any(|t| &t.label == host) {
+            continue;
         }
+        targets.push(matrix::MatrixTarget {
+            label: host.clone(),
+            host: host.clone(),
+            options: probe.scan_options(batch, config),
+        });
+    }
+    
+    if targets.is_empty() {
+        return Err(anyhow::anyhow!("Minimal satu target: -t HOST1,HOST2, --profiles, atau --all-profiles"));
+    }
+    Ok(targets)
+}
+
+// Brute-force subdomain dari wordlist, return yang resolve + label sumber
+async fn wordlist_candidates(
+    config: &config::Config,
+    domain: &str,
+    file: Option<&str>,
+    scan_options: &scanner::ScanOptions,
+    running: Arc<AtomicBool>,
+) -> anyhow::Result<(Vec<String>, String)> {
+    let (content, source) = match file {
+        Some(path) => (
+            std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("Gagal membaca {}: {}", path, e))?,
+            path.to_string(),
+        ),
+        None => wordlist::resolve_wordlist(config)?,
+    };
+    let words = wordlist::parse_words(&content);
+    
+    outln!("\n{} {} ({} kata)", "📖 Wordlist:".cyan(), source, words.len());
+    outln!("{}", "🔎 Resolving kandidat subdomain...".cyan());
+    
+    let subdomains = wordlist::enumerate_subdomains(domain, &words, scan_options, running).await;
+    outln!("\n{} {} subdomains resolve\n", "✓".green(), subdomains.len());
+    
+    Ok((subdomains, format!("wordlist:{}", source)))
+}
+
+// Batch test daftar subdomain ke target, lalu simpan ke riwayat + export
+async fn test_and_save(
+    target: &str,
+    domain: &str,
+    source: &str,
+    subdomains: &[String],
+    scan_options: &scanner::ScanOptions,
+    format: results::ExportFormat,
+    running: Arc<AtomicBool>,
+) -> anyhow::Result<Vec<scanner::ScanResult>> {
+    if subdomains.is_empty() {
+        outln!("{}", "Tidak ada subdomain ditemukan".yellow());
+        return Ok(Vec::new());
+    }
+    
+    let results = scanner::batch_test(target, subdomains, scan_options, running).await?;
+    let meta = results::ScanMeta::new(target, domain, source, scan_options.timeout);
+    save_results(&results, &meta, format)?;
+    
+    Ok(results)
+}
+
+// Quick check target status lewat probe rustls native (tanpa curl):
+// TLS ke target:443, fallback TCP connect ke port 80
+async fn check_target_quick(target: &str, timeout: u64, resolver: &dns::Resolver) -> bool {
+    let ip = match resolver.resolve_first(target).await {
+        Ok(ip) => ip,
+        Err(_) => return false,
+    };
+    let Ok(addr) = ip.parse::<std::net::IpAddr>() else {
+        return false;
+    };
+    let timeout = Duration::from_secs(timeout);
+    
+    // Server yang menjawab TLS (walau cert tidak cocok / alert) berarti online
+    let tls = probe::tls_probe(addr, 443, target, timeout).await;
+    if !matches!(
+        tls.outcome,
+        probe::TlsOutcome::ConnectFailed(_) | probe::TlsOutcome::Timeout | probe::TlsOutcome::Reset
+    ) {
+        return true;
     }
     
-    false
+    probe::tcp_connect(addr, 80, timeout).await.is_ok()
 }
@@ -0,0 +1,342 @@
+use chrono::Local;
+use colored::Colorize;
+use serde::Serialize;
+use std::fs;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::{out, outln};
+use crate::cdn::Provider;
+use crate::config::Config;
+use crate::dns;
+use crate::results::{ExportFormat, ScanMeta};
+use crate::scanner::{self, Job, ScanOptions, ScanResult};
+use crate::ui::{self, OutputMode};
+
+/// Satu kolom matrix: target dengan opsi probe-nya sendiri (bisa dari profile)
+#[derive(Debug, Clone)]
+pub struct MatrixTarget {
+    /// Nama profile atau host target
+    pub label: String,
+    pub host: String,
+    pub options: ScanOptions,
+}
+
+/// Hasil satu subdomain ke satu target
+#[derive(Debug, Clone, Serialize)]
+pub struct MatrixCell {
+    pub target: String,
+    pub is_working: bool,
+    pub status_code: Option<u16>,
+    pub port: Option<u16>,
+    pub latency_ms: Option<u64>,
+    pub error_msg: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MatrixRow {
+    pub subdomain: String,
+    /// Kosong = DNS gagal
+    pub ip: String,
+    pub provider: Option<Provider>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub cname_chain: Vec<String>,
+    pub cname_provider: Option<Provider>,
+    pub cells: Vec<MatrixCell>,
+}
+
+impl MatrixRow {
+    fn new(results: &[ScanResult], targets: &[MatrixTarget]) -> Self {
+        let first = &results[0];
+        Self {
+            subdomain: first.subdomain.clone(),
+            ip: first.ip.clone(),
+            provider: first.provider,
+            cname_chain: first.cname_chain.clone(),
+            cname_provider: first.cname_provider,
+            cells: results
+                .iter()
+                .zip(targets)
+                .map(|(result, target)| MatrixCell {
+                    target: target.label.clone(),
+                    is_working: result.is_working,
+                    status_code: result.status_code,
+                    port: result.port,
+                    latency_ms: result.latency_ms,
+                    error_msg: result.error_msg.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    fn dns_failed(&self) -> bool {
+        self.ip.is_empty()
+    }
+}
+
+/// Isi file export matrix
+#[derive(Debug, Clone, Serialize)]
+pub struct MatrixReport {
+    pub meta: ScanMeta,
+    pub targets: Vec<String>,
+    pub rows: Vec<MatrixRow>,
+}
+
+impl MatrixReport {
+    pub fn new(meta: ScanMeta, targets: &[MatrixTarget], grid: &[Vec<ScanResult>]) -> Self {
+        Self {
+            meta,
+            targets: targets.iter().map(|t| t.label.clone()).collect(),
+            rows: grid.iter().map(|results| MatrixRow::new(results, targets)).collect(),
+        }
+    }
+
+    pub fn any_working(&self) -> bool {
+        self.rows.iter().any(|row| row.cells.iter().any(|c| c.is_working))
+    }
+
+    pub fn all_dns_failed(&self) -> bool {
+        !self.rows.is_empty() && self.rows.iter().all(MatrixRow::dns_failed)
+    }
+}
+
+/// Hasil satu target (kolom) sebagai daftar ScanResult biasa, untuk riwayat
+pub fn column(grid: &[Vec<ScanResult>], index: usize) -> Vec<ScanResult> {
+    grid.iter().map(|results| results[index].clone()).collect()
+}
+
+/// Test semua subdomain ke semua target. DNS di-resolve sekali per subdomain,
+/// lalu IP-nya diprobe ke tiap target bersamaan. Return [subdomain][target]
+pub async fn run(
+    targets: &[MatrixTarget],
+    subdomains: &[String],
+    options: &ScanOptions,
+    running: Arc<AtomicBool>,
+) -> Vec<Vec<ScanResult>> {
+    outln!("\n{}", "Starting matrix test...".cyan());
+    outln!(
+        "{} {} subdomains × {} targets",
+        "Total:".bright_black(),
+        subdomains.len().to_string().yellow(),
+        targets.len().to_string().yellow()
+    );
+    outln!(
+        "{} {} | {} {}\n",
+        "Concurrency:".bright_black(),
+        options.concurrency.max(1).to_string().yellow(),
+        "Rate limit:".bright_black(),
+        match options.rate_limit {
+            Some(rps) if rps > 0.0 => format!("{} req/s", rps).yellow(),
+            _ => "unlimited".yellow(),
+        }
+    );
+
+    // Satu slot rate limit per subdomain (DNS + semua target)
+    let grid = scanner::worker_pool(
+        subdomains.iter().collect(),
+        options,
+        &running,
+        |subdomain| {
+            Job::Run(format!("Testing: {}", subdomain), async move {
+                match scanner::resolve_subdomain(subdomain, options).await {
+                    Ok(answer) => {
+                        futures::future::join_all(targets.iter().map(|target| {
+                            scanner::with_retries(&target.options, || async {
+                                let deadline = Instant::now() + Duration::from_secs(target.options.timeout);
+                                scanner::probe_until(deadline, &target.host, subdomain, &answer.ips, &target.options)
+                                    .await
+                                    .with_cnames(&answer.cnames)
+                            })
+                        }))
+                        .await
+                    }
+                    Err(e) => vec![ScanResult::dns_failed(subdomain, e); targets.len()],
+                }
+            })
+        },
+        |results: &Vec<ScanResult>| emit(&MatrixRow::new(results, targets)),
+    )
+    .await;
+
+    grid
+}
+
+// Mode --json: satu baris matrix per subdomain, --quiet: "subdomain target" yang working
+fn emit(row: &MatrixRow) {
+    match ui::output_mode() {
+        OutputMode::Json => {
+            if let Ok(line) = serde_json::to_string(row) {
+                println!("{}", line);
+            }
+        }
+        OutputMode::Quiet => {
+            for cell in row.cells.iter().filter(|c| c.is_working) {
+                println!("{} {}", row.subdomain, cell.target);
+            }
+        }
+        OutputMode::Text => {}
+    }
+}
+
+fn cell_symbol(row: &MatrixRow, cell: &MatrixCell) -> &'static str {
+    if row.dns_failed() {
+        "-"
+    } else if cell.is_working {
+        "✓"
+    } else {
+        "✗"
+    }
+}
+
+/// Tabel ringkas: baris = subdomain, kolom T1..Tn = target
+pub fn print_table(report: &MatrixReport) {
+    let width = report.rows.iter().map(|r| r.subdomain.len()).max().unwrap_or(9).clamp(9, 40);
+
+    outln!("\n{}", "═".repeat(60).cyan());
+    ui::center_text("MATRIX SCAN");
+    outln!("{}", "═".repeat(60).cyan());
+
+    outln!();
+    for (idx, target) in report.targets.iter().enumerate() {
+        outln!("{} {}", format!("T{}", idx + 1).cyan().bold(), target);
+    }
+
+    out!("\n{:<width$} {:<16}", "Subdomain".bold(), "IP".bold(), width = width);
+    for idx in 0..report.targets.len() {
+        out!(" {:^4}", format!("T{}", idx + 1).bold());
+    }
+    outln!();
+    outln!("{}", "─".repeat(width + 17 + report.targets.len() * 5).bright_black());
+
+    for row in &report.rows {
+        let ip = if row.dns_failed() { "DNS failed" } else { row.ip.as_str() };
+        out!("{:<width$} {:<16}", row.subdomain, ip.bright_black(), width = width);
+        for cell in &row.cells {
+            let symbol = cell_symbol(row, cell);
+            let symbol = match symbol {
+                "✓" => symbol.green(),
+                "✗" => symbol.red(),
+                _ => symbol.bright_black(),
+            };
+            out!(" {:^4}", symbol);
+        }
+        outln!();
+    }
+
+    outln!("\n{}", "─".repeat(60).bright_black());
+    outln!("Working per target:");
+    for (idx, target) in report.targets.iter().enumerate() {
+        let working = report.rows.iter().filter(|r| r.cells[idx].is_working).count();
+        outln!(
+            "  {} {} {}/{}",
+            format!("T{}", idx + 1).cyan(),
+            target,
+            working.to_string().green(),
+            report.rows.len()
+        );
+    }
+    let dns_failed = report.rows.iter().filter(|r| r.dns_failed()).count();
+    outln!("  DNS failed: {}", dns_failed);
+    scanner::print_dns_stats(&dns::Resolver::shared());
+    outln!("{}", "─".repeat(60).bright_black());
+}
+
+fn render_text(report: &MatrixReport) -> String {
+    let meta = &report.meta;
+    let mut content = String::new();
+    content.push_str("InjectTools Matrix Scan\n");
+    content.push_str(&format!("Version: {}\n", meta.tool_version));
+    content.push_str(&format!("Date: {}\n", meta.timestamp));
+    content.push_str(&format!("Domain: {}\n", meta.domain));
+    content.push_str(&format!("Source: {}\n", meta.source));
+    content.push_str(&format!("Timeout: {}s\n", meta.timeout));
+    content.push_str(&format!("Resolver: {}\n\n", meta.resolver));
+
+    for (idx, target) in report.targets.iter().enumerate() {
+        content.push_str(&format!("T{} {}\n", idx + 1, target));
+    }
+    content.push('\n');
+
+    for row in &report.rows {
+        let ip = if row.dns_failed() { "DNS failed" } else { row.ip.as_str() };
+        content.push_str(&format!("{:<40} {:<16}", row.subdomain, ip));
+        for cell in &row.cells {
+            content.push_str(&format!(" {}", cell_symbol(row, cell)));
+        }
+        content.push('\n');
+    }
+    content
+}
+
+// CSV lebar: satu kolom per target berisi working / failed / dns_failed
+fn render_csv(report: &MatrixReport) -> anyhow::Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+
+    let mut header = vec![
+        "subdomain".to_string(),
+        "ip".to_string(),
+        "provider".to_string(),
+        "cname_chain".to_string(),
+        "cname_provider".to_string(),
+    ];
+    header.extend(report.targets.iter().cloned());
+    writer.write_record(&header)?;
+
+    for row in &report.rows {
+        let mut record = vec![
+            row.subdomain.clone(),
+            row.ip.clone(),
+            row.provider.map(|p| p.name().to_string()).unwrap_or_default(),
+            row.cname_chain.join(" > "),
+            row.cname_provider.map(|p| p.name().to_string()).unwrap_or_default(),
+        ];
+        record.extend(row.cells.iter().map(|cell| {
+            match cell_symbol(row, cell) {
+                "✓" => "working",
+                "✗" => "failed",
+                _ => "dns_failed",
+            }
+            .to_string()
+        }));
+        writer.write_record(&record)?;
+    }
+
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+/// Export ke <results>/matrix/ (terpisah dari export scan biasa)
+pub fn export(report: &MatrixReport, format: ExportFormat) -> anyhow::Result<()> {
+    let matrix_dir = Config::results_dir().join("matrix");
+    fs::create_dir_all(&matrix_dir)?;
+
+    let timestamp = Local::now().format("%Y%m%d_%H%M%S");
+    let filename = format!(
+        "matrix_{}_{}.{}",
+        report.meta.domain.replace(".", "_"),
+        timestamp,
+        format.extension()
+    );
+    let filepath = matrix_dir.join(&filename);
+
+    let content = match format {
+        ExportFormat::Txt => render_text(report),
+        ExportFormat::Json => serde_json::to_string_pretty(report)?,
+        ExportFormat::Ndjson => {
+            let mut content = String::new();
+            for row in &report.rows {
+                content.push_str(&serde_json::to_string(row)?);
+                content.push('\n');
+            }
+            content
+        }
+        ExportFormat::Csv => render_csv(report)?,
+    };
+
+    fs::write(&filepath, content)?;
+
+    outln!("\n{} {}", "📁 Matrix exported:".green(), filepath.display().to_string().bright_black());
+
+    Ok(())
+}
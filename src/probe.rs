@@ -0,0 +1,518 @@
+use std::fmt;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
+
+use base64::Engine;
+use rustls::client::{ServerCertVerified, ServerCertVerifier, WebPkiVerifier};
+use rustls::{Certificate, CertificateError, ClientConfig, OwnedTrustAnchor, RootCertStore, ServerName};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::TlsConnector;
+
+/// Hasil nyata dari TLS handshake ke IP subdomain dengan SNI target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TlsOutcome {
+    HandshakeOk,
+    CertMismatch,
+    Alert(String),
+    Reset,
+    Timeout,
+    ConnectFailed(String),
+    Failed(String),
+}
+
+impl TlsOutcome {
+    pub fn is_ok(&self) -> bool {
+        matches!(self, TlsOutcome::HandshakeOk)
+    }
+
+    /// Gagal di level jaringan (timeout, reset, TCP connect), bisa beda kalau diulang
+    pub fn is_transient(&self) -> bool {
+        matches!(self, TlsOutcome::Timeout | TlsOutcome::Reset | TlsOutcome::ConnectFailed(_))
+    }
+}
+
+impl fmt::Display for TlsOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TlsOutcome::HandshakeOk => write!(f, "SSL handshake success"),
+            TlsOutcome::CertMismatch => write!(f, "Certificate tidak cocok dengan SNI"),
+            TlsOutcome::Alert(alert) => write!(f, "TLS alert: {}", alert),
+            TlsOutcome::Reset => write!(f, "Connection reset"),
+            TlsOutcome::Timeout => write!(f, "Handshake timeout"),
+            TlsOutcome::ConnectFailed(e) => write!(f, "TCP connect failed: {}", e),
+            TlsOutcome::Failed(e) => write!(f, "SSL handshake failed: {}", e),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TlsProbe {
+    pub outcome: TlsOutcome,
+    pub latency_ms: Option<u64>,
+}
+
+// Root store dibangun sekali saja, dipakai ulang untuk semua probe
+fn webpki_verifier() -> Arc<WebPkiVerifier> {
+    static VERIFIER: OnceLock<Arc<WebPkiVerifier>> = OnceLock::new();
+    VERIFIER
+        .get_or_init(|| {
+            let mut roots = RootCertStore::empty();
+            roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
+                OwnedTrustAnchor::from_subject_spki_name_constraints(
+                    ta.subject,
+                    ta.spki,
+                    ta.name_constraints,
+                )
+            }));
+            Arc::new(WebPkiVerifier::new(roots, None))
+        })
+        .clone()
+}
+
+// Verifier yang tidak pernah menggagalkan handshake (seperti openssl s_client),
+// tapi mencatat apakah certificate cocok dengan SNI
+struct RecordingVerifier {
+    inner: Arc<WebPkiVerifier>,
+    name_mismatch: Mutex<bool>,
+}
+
+impl ServerCertVerifier for RecordingVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        intermediates: &[Certificate],
+        server_name: &ServerName,
+        scts: &mut dyn Iterator<Item = &[u8]>,
+        ocsp_response: &[u8],
+        now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let result = self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            scts,
+            ocsp_response,
+            now,
+        );
+
+        // webpki cek chain dulu baru nama, jadi cert self-signed / issuer
+        // tidak dikenal tetap dicek namanya secara terpisah
+        let mismatch = match result {
+            Err(rustls::Error::InvalidCertificate(CertificateError::NotValidForName)) => true,
+            Err(_) => !cert_matches_name(end_entity, server_name),
+            Ok(_) => false,
+        };
+        if mismatch {
+            *self.name_mismatch.lock().unwrap() = true;
+        }
+
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+fn cert_matches_name(cert: &Certificate, server_name: &ServerName) -> bool {
+    let ServerName::DnsName(name) = server_name else {
+        return true;
+    };
+    let Ok(cert) = webpki::EndEntityCert::try_from(cert.0.as_slice()) else {
+        return false;
+    };
+    match webpki::SubjectNameRef::try_from_ascii_str(name.as_ref()) {
+        Ok(subject) => cert.verify_is_valid_for_subject_name(subject).is_ok(),
+        Err(_) => false,
+    }
+}
+
+fn classify_io_error(err: &io::Error) -> TlsOutcome {
+    if let Some(tls_err) = err.get_ref().and_then(|e| e.downcast_ref::<rustls::Error>()) {
+        return match tls_err {
+            rustls::Error::AlertReceived(alert) => TlsOutcome::Alert(format!("{:?}", alert)),
+            other => TlsOutcome::Failed(other.to_string()),
+        };
+    }
+
+    match err.kind() {
+        io::ErrorKind::ConnectionReset
+        | io::ErrorKind::ConnectionAborted
+        | io::ErrorKind::UnexpectedEof
+        | io::ErrorKind::BrokenPipe => TlsOutcome::Reset,
+        io::ErrorKind::TimedOut => TlsOutcome::Timeout,
+        _ => TlsOutcome::Failed(err.to_string()),
+    }
+}
+
+/// Connect ke `ip:port` dan lakukan TLS handshake dengan SNI `sni`.
+/// Stream dikembalikan supaya bisa dipakai untuk request berikutnya.
+pub async fn tls_connect(
+    ip: IpAddr,
+    port: u16,
+    sni: &str,
+    timeout: Duration,
+) -> Result<(TlsStream<TcpStream>, u64), TlsOutcome> {
+    let server_name = ServerName::try_from(sni)
+        .map_err(|_| TlsOutcome::Failed(format!("SNI tidak valid: {}", sni)))?;
+
+    let verifier = Arc::new(RecordingVerifier {
+        inner: webpki_verifier(),
+        name_mismatch: Mutex::new(false),
+    });
+
+    let config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(verifier.clone())
+        .with_no_client_auth();
+    let connector = TlsConnector::from(Arc::new(config));
+
+    let addr = SocketAddr::new(ip, port);
+    let start = Instant::now();
+
+    let handshake = async {
+        let tcp = TcpStream::connect(addr)
+            .await
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::TimedOut => TlsOutcome::Timeout,
+                _ => TlsOutcome::ConnectFailed(e.to_string()),
+            })?;
+        tcp.set_nodelay(true).ok();
+
+        connector
+            .connect(server_name, tcp)
+            .await
+            .map_err(|e| classify_io_error(&e))
+    };
+
+    let stream = match tokio::time::timeout(timeout, handshake).await {
+        Ok(result) => result?,
+        Err(_) => return Err(TlsOutcome::Timeout),
+    };
+    let latency = start.elapsed().as_millis() as u64;
+
+    if *verifier.name_mismatch.lock().unwrap() {
+        return Err(TlsOutcome::CertMismatch);
+    }
+
+    Ok((stream, latency))
+}
+
+/// TLS handshake probe tanpa request lanjutan
+pub async fn tls_probe(ip: IpAddr, port: u16, sni: &str, timeout: Duration) -> TlsProbe {
+    match tls_connect(ip, port, sni, timeout).await {
+        Ok((_, latency)) => TlsProbe {
+            outcome: TlsOutcome::HandshakeOk,
+            latency_ms: Some(latency),
+        },
+        Err(outcome) => TlsProbe {
+            outcome,
+            latency_ms: None,
+        },
+    }
+}
+
+const MAX_HEAD_SIZE: usize = 64 * 1024;
+const MAX_BODY_SIZE: usize = 4 * 1024 * 1024;
+
+/// Status line + header dari HTTP response
+#[derive(Debug, Clone)]
+pub struct ResponseHead {
+    pub status_code: u16,
+    pub headers: Vec<(String, String)>,
+}
+
+impl ResponseHead {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Hasil HTTP request lewat tunnel SNI
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status_code: u16,
+    pub server: Option<String>,
+    pub cf_ray: Option<String>,
+    pub body_size: usize,
+}
+
+impl HttpResponse {
+    /// Response dianggap sampai ke target kalau edge tidak menolak request.
+    /// 400/403/409/421 adalah jawaban khas edge CDN untuk Host/SNI yang
+    /// tidak dilayani (direct IP access, cross-user, misdirected request);
+    /// status lain (termasuk 404/5xx dari origin) berarti request sudah diroute.
+    pub fn routes_to_target(&self) -> bool {
+        !matches!(self.status_code, 400 | 403 | 409 | 421)
+    }
+}
+
+// Baca sampai akhir header, sisa byte (awal body) dikembalikan
+async fn read_response_head<S>(stream: &mut S) -> Result<(ResponseHead, Vec<u8>), String>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut buf = Vec::with_capacity(4096);
+    let mut chunk = [0u8; 4096];
+
+    let head_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        if buf.len() > MAX_HEAD_SIZE {
+            return Err("HTTP header terlalu besar".to_string());
+        }
+        let n = stream.read(&mut chunk).await.map_err(|e| classify_io_error(&e).to_string())?;
+        if n == 0 {
+            return Err("Connection ditutup sebelum HTTP response".to_string());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&buf[..head_end]).to_string();
+    let mut lines = head.split("\r\n");
+
+    // HTTP/1.1 200 OK
+    let status_line = lines.next().unwrap_or_default();
+    let mut parts = status_line.split_whitespace();
+    let status_code = match (parts.next(), parts.next()) {
+        (Some(version), Some(code)) if version.starts_with("HTTP/") => code
+            .parse::<u16>()
+            .map_err(|_| format!("Status line tidak valid: {}", status_line))?,
+        _ => return Err(format!("Bukan HTTP response: {}", status_line)),
+    };
+
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect();
+
+    Ok((ResponseHead { status_code, headers }, buf[head_end + 4..].to_vec()))
+}
+
+// Hitung ukuran data dari body chunked (tanpa framing)
+fn chunked_body_size(body: &[u8]) -> usize {
+    let mut size = 0;
+    let mut rest = body;
+
+    while let Some(line_end) = rest.windows(2).position(|w| w == b"\r\n") {
+        let line = String::from_utf8_lossy(&rest[..line_end]);
+        let hex = line.split(';').next().unwrap_or_default().trim();
+        let chunk_size = match usize::from_str_radix(hex, 16) {
+            Ok(n) => n,
+            Err(_) => break,
+        };
+        if chunk_size == 0 {
+            break;
+        }
+
+        // Chunk terpotong (timeout / batas body): hitung byte yang benar-benar diterima saja
+        let data = &rest[line_end + 2..];
+        size += chunk_size.min(data.len());
+
+        let skip = chunk_size + 2;
+        if skip > data.len() {
+            break;
+        }
+        rest = &data[skip..];
+    }
+
+    size
+}
+
+async fn read_body<S>(stream: &mut S, head: &ResponseHead, mut body: Vec<u8>) -> usize
+where
+    S: AsyncRead + Unpin,
+{
+    let content_length = head
+        .header("content-length")
+        .and_then(|v| v.parse::<usize>().ok());
+    let chunked = head
+        .header("transfer-encoding")
+        .map(|v| v.to_ascii_lowercase().contains("chunked"))
+        .unwrap_or(false);
+
+    let mut chunk = [0u8; 8192];
+    loop {
+        if let Some(len) = content_length {
+            if body.len() >= len {
+                break;
+            }
+        }
+        if chunked && body.ends_with(b"0\r\n\r\n") {
+            break;
+        }
+        if body.len() >= MAX_BODY_SIZE {
+            break;
+        }
+        // Connection: close, jadi EOF / reset = akhir body
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => body.extend_from_slice(&chunk[..n]),
+        }
+    }
+
+    if chunked {
+        chunked_body_size(&body)
+    } else {
+        content_length.map(|len| len.min(body.len())).unwrap_or(body.len())
+    }
+}
+
+/// Kirim HTTP GET lewat stream yang sudah terhubung (misal setelah TLS
+/// handshake dengan SNI target) dan baca response-nya
+pub async fn http_probe<S>(
+    stream: &mut S,
+    host: &str,
+    path: &str,
+    timeout: Duration,
+) -> Result<HttpResponse, String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: InjectTools/{}\r\nAccept: */*\r\nConnection: close\r\n\r\n",
+        path,
+        host,
+        env!("CARGO_PKG_VERSION")
+    );
+
+    let exchange = async {
+        stream
+            .write_all(request.as_bytes())
+            .await
+            .map_err(|e| classify_io_error(&e).to_string())?;
+        stream.flush().await.map_err(|e| classify_io_error(&e).to_string())?;
+
+        let (head, body) = read_response_head(stream).await?;
+        let body_size = read_body(stream, &head, body).await;
+
+        Ok(HttpResponse {
+            status_code: head.status_code,
+            server: head.header("server").map(str::to_string),
+            cf_ray: head.header("cf-ray").map(str::to_string),
+            body_size,
+        })
+    };
+
+    tokio::time::timeout(timeout, exchange)
+        .await
+        .map_err(|_| "HTTP response timeout".to_string())?
+}
+
+/// Koneksi TCP biasa (tanpa TLS), untuk target ws:// / http:// atau test lokal
+pub async fn tcp_connect(
+    ip: IpAddr,
+    port: u16,
+    timeout: Duration,
+) -> Result<(TcpStream, u64), TlsOutcome> {
+    let start = Instant::now();
+    match tokio::time::timeout(timeout, TcpStream::connect(SocketAddr::new(ip, port))).await {
+        Ok(Ok(stream)) => {
+            stream.set_nodelay(true).ok();
+            Ok((stream, start.elapsed().as_millis() as u64))
+        }
+        Ok(Err(e)) => Err(match e.kind() {
+            io::ErrorKind::TimedOut => TlsOutcome::Timeout,
+            io::ErrorKind::ConnectionReset => TlsOutcome::Reset,
+            _ => TlsOutcome::ConnectFailed(e.to_string()),
+        }),
+        Err(_) => Err(TlsOutcome::Timeout),
+    }
+}
+
+/// HTTP/1.1 `Upgrade: websocket` handshake lewat stream yang sudah terhubung.
+/// Hanya `101 Switching Protocols` yang berarti tunnel WS bisa dipakai.
+pub async fn ws_probe<S>(
+    stream: &mut S,
+    host: &str,
+    path: &str,
+    timeout: Duration,
+) -> Result<HttpResponse, String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let key = base64::engine::general_purpose::STANDARD.encode(rand::random::<[u8; 16]>());
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: InjectTools/{}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n\r\n",
+        path,
+        host,
+        env!("CARGO_PKG_VERSION"),
+        key
+    );
+
+    let exchange = async {
+        stream
+            .write_all(request.as_bytes())
+            .await
+            .map_err(|e| classify_io_error(&e).to_string())?;
+        stream.flush().await.map_err(|e| classify_io_error(&e).to_string())?;
+
+        // Setelah 101 koneksi jadi frame WS, jadi body tidak dibaca
+        let (head, body) = read_response_head(stream).await?;
+        let body_size = if head.status_code == 101 {
+            0
+        } else {
+            read_body(stream, &head, body).await
+        };
+
+        Ok(HttpResponse {
+            status_code: head.status_code,
+            server: head.header("server").map(str::to_string),
+            cf_ray: head.header("cf-ray").map(str::to_string),
+            body_size,
+        })
+    };
+
+    tokio::time::timeout(timeout, exchange)
+        .await
+        .map_err(|_| "WebSocket handshake timeout".to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunked_body_size_sums_chunks() {
+        assert_eq!(chunked_body_size(b"4\r\nWiki\r\n5;ext=1\r\npedia\r\n0\r\n\r\n"), 9);
+        assert_eq!(chunked_body_size(b"A\r\n0123456789\r\n0\r\n\r\n"), 10);
+        assert_eq!(chunked_body_size(b"0\r\n\r\n"), 0);
+    }
+
+    #[test]
+    fn chunked_body_size_stops_on_truncated_or_invalid_input() {
+        // Chunk 0x10 baru terkirim 5 byte: hanya 3 + 5 yang dihitung, chunk berikutnya tidak dibaca
+        assert_eq!(chunked_body_size(b"3\r\nabc\r\n10\r\nshort"), 3 + 5);
+        assert_eq!(chunked_body_size(b"3\r\nabc\r\n10\r\n0123456789abcdef"), 3 + 16);
+        assert_eq!(chunked_body_size(b"zz\r\nabc\r\n"), 0);
+        assert_eq!(chunked_body_size(b""), 0);
+    }
+
+    #[tokio::test]
+    async fn read_response_head_parses_status_and_headers() {
+        let mut raw: &[u8] = b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nCF-RAY: abc-SIN\r\n\r\nrest";
+        let (head, body) = read_response_head(&mut raw).await.unwrap();
+
+        assert_eq!(head.status_code, 101);
+        assert_eq!(head.header("upgrade"), Some("websocket"));
+        assert_eq!(head.header("cf-ray"), Some("abc-SIN"));
+        assert_eq!(head.header("server"), None);
+        assert_eq!(body, b"rest");
+    }
+
+    #[tokio::test]
+    async fn read_response_head_rejects_non_http() {
+        let mut raw: &[u8] = b"SSH-2.0-OpenSSH_9.6\r\n\r\n";
+        assert!(read_response_head(&mut raw).await.unwrap_err().starts_with("Bukan HTTP response"));
+
+        let mut raw: &[u8] = b"HTTP/1.1 abc OK\r\n\r\n";
+        assert!(read_response_head(&mut raw).await.unwrap_err().starts_with("Status line tidak valid"));
+
+        let mut raw: &[u8] = b"HTTP/1.1 200 OK\r\n";
+        assert!(read_response_head(&mut raw).await.is_err());
+    }
+}
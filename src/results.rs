@@ -1,139 +1,717 @@
+use crate::outln;
+use crate::cdn::{self, Provider};
 use crate::config::Config;
-use crate::scanner::ScanResult;
+use crate::dns;
+use crate::history;
+use crate::ui::{self, OutputMode};
+use crate::scanner::{IpResult, ScanResult};
 use chrono::Local;
 use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fs;
+use std::path::{Path, PathBuf};
 
-pub fn export_results(results: &[ScanResult], domain: &str) -> anyhow::Result<()> {
+/// Format file export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// Laporan teks (default)
+    Txt,
+    /// JSON: metadata + hasil
+    Json,
+    /// CSV satu baris per subdomain
+    Csv,
+    /// JSON per baris, satu hasil per baris
+    Ndjson,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 4] = [
+        ExportFormat::Txt,
+        ExportFormat::Json,
+        ExportFormat::Csv,
+        ExportFormat::Ndjson,
+    ];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Txt => "txt",
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?;
+        Self::ALL.into_iter().find(|f| f.extension() == ext)
+    }
+}
+
+/// Metadata scan yang ikut diexport
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanMeta {
+    pub tool_version: String,
+    pub timestamp: String,
+    pub target: String,
+    pub domain: String,
+    /// Asal daftar subdomain: crtsh, wordlist:<file>, cleanip, ...
+    pub source: String,
+    pub timeout: u64,
+    /// DNS server yang dipakai (--resolver)
+    #[serde(default)]
+    pub resolver: String,
+}
+
+impl ScanMeta {
+    pub fn new(target: &str, domain: &str, source: &str, timeout: u64) -> Self {
+        Self {
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            timestamp: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            target: target.to_string(),
+            domain: domain.to_string(),
+            source: source.to_string(),
+            timeout,
+            resolver: dns::Resolver::shared().label().to_string(),
+        }
+    }
+}
+
+/// Isi file JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanReport {
+    pub meta: ScanMeta,
+    pub results: Vec<ScanResult>,
+}
+
+// Baris CSV: breakdown per IP diringkas jadi daftar dipisah ';'
+#[derive(Debug, Serialize, Deserialize)]
+struct CsvRow {
+    subdomain: String,
+    ip: String,
+    #[serde(default)]
+    port: Option<u16>,
+    provider: String,
+    is_working: bool,
+    status_code: Option<u16>,
+    server: Option<String>,
+    cf_ray: Option<String>,
+    body_size: Option<usize>,
+    latency_ms: Option<u64>,
+    error_msg: Option<String>,
+    ips: String,
+    working_ips: String,
+    /// Rantai CNAME dipisah " > "
+    #[serde(default)]
+    cname_chain: String,
+    #[serde(default)]
+    cname_provider: String,
+    /// Parent wildcard DNS, kosong = bukan wildcard
+    #[serde(default)]
+    wildcard: String,
+}
+
+impl From<&ScanResult> for CsvRow {
+    fn from(result: &ScanResult) -> Self {
+        Self {
+            subdomain: result.subdomain.clone(),
+            ip: result.ip.clone(),
+            port: result.port,
+            provider: result.provider.map(|p| p.name().to_string()).unwrap_or_default(),
+            is_working: result.is_working,
+            status_code: result.status_code,
+            server: result.server.clone(),
+            cf_ray: result.cf_ray.clone(),
+            body_size: result.body_size,
+            latency_ms: result.latency_ms,
+            error_msg: result.error_msg.clone(),
+            ips: result.ip_results.iter().map(|r| r.ip.as_str()).collect::<Vec<_>>().join(";"),
+            working_ips: result.working_ips().join(";"),
+            cname_chain: result.cname_chain.join(" > "),
+            cname_provider: result.cname_provider.map(|p| p.name().to_string()).unwrap_or_default(),
+            wildcard: result.wildcard.clone().unwrap_or_default(),
+        }
+    }
+}
+
+impl From<CsvRow> for ScanResult {
+    fn from(row: CsvRow) -> Self {
+        let working: HashSet<&str> = row.working_ips.split(';').filter(|s| !s.is_empty()).collect();
+        let ip_results = row
+            .ips
+            .split(';')
+            .filter(|s| !s.is_empty())
+            .map(|ip| IpResult {
+                ip: ip.to_string(),
+                provider: cdn::lookup(ip),
+                is_working: working.contains(ip),
+                status_code: None,
+                latency_ms: None,
+                error_msg: None,
+            })
+            .collect();
+
+        Self {
+            subdomain: row.subdomain,
+            ip: row.ip,
+            port: row.port,
+            provider: row.provider.parse().ok(),
+            cname_chain: split_cnames(&row.cname_chain),
+            cname_provider: row.cname_provider.parse().ok(),
+            wildcard: (!row.wildcard.is_empty()).then_some(row.wildcard),
+            is_working: row.is_working,
+            status_code: row.status_code,
+            server: row.server,
+            cf_ray: row.cf_ray,
+            body_size: row.body_size,
+            latency_ms: row.latency_ms,
+            error_msg: row.error_msg,
+            ip_results,
+            retryable: false,
+        }
+    }
+}
+
+pub fn export_results(results: &[ScanResult], meta: &ScanMeta, format: ExportFormat) -> anyhow::Result<()> {
     let results_dir = Config::results_dir();
     fs::create_dir_all(&results_dir)?;
-    
+
     let timestamp = Local::now().format("%Y%m%d_%H%M%S");
-    let filename = format!("scan_{}_{}.txt", domain.replace(".", "_"), timestamp);
+    let filename = format!(
+        "scan_{}_{}.{}",
+        meta.domain.replace(".", "_"),
+        timestamp,
+        format.extension()
+    );
     let filepath = results_dir.join(&filename);
-    
+
+    let content = match format {
+        ExportFormat::Txt => render_text(Some(meta), results),
+        ExportFormat::Json => serde_json::to_string_pretty(&ScanReport {
+            meta: meta.clone(),
+            results: results.to_vec(),
+        })?,
+        ExportFormat::Ndjson => {
+            let mut content = String::new();
+            for result in results {
+                content.push_str(&serde_json::to_string(result)?);
+                content.push('\n');
+            }
+            content
+        }
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            for result in results {
+                writer.serialize(CsvRow::from(result))?;
+            }
+            String::from_utf8(writer.into_inner()?)?
+        }
+    };
+
+    fs::write(&filepath, content)?;
+
+    let working = results.iter().filter(|r| r.is_working).count();
+    outln!("\n{}", "═".repeat(60).green());
+    outln!("{}", "📁 RESULTS EXPORTED".green().bold());
+    outln!("{}", "═".repeat(60).green());
+    outln!("\n{} {}", "File:".bright_black(), filename.green());
+    outln!("{} {}", "Path:".bright_black(), filepath.display().to_string().bright_black());
+    outln!("{} {} bugs\n", "Working:".bright_black(), working.to_string().green());
+
+    Ok(())
+}
+
+/// Tulis satu hasil ke stdout untuk mode --json / --quiet
+pub fn emit(result: &ScanResult) {
+    match ui::output_mode() {
+        OutputMode::Json => {
+            if let Ok(line) = serde_json::to_string(result) {
+                println!("{}", line);
+            }
+        }
+        OutputMode::Quiet if result.is_working => println!("{}", result.subdomain),
+        _ => {}
+    }
+}
+
+/// Hasil scan yang dimuat ulang: file export atau scan di riwayat
+#[derive(Debug, Clone)]
+pub struct ScanSet {
+    /// Nama file atau "history #id"
+    pub label: String,
+    pub meta: Option<ScanMeta>,
+    pub results: Vec<ScanResult>,
+    /// Laporan txt lama: hanya host working dan non-Cloudflare, host gagal tidak ada
+    pub partial: bool,
+}
+
+impl ScanSet {
+    /// `#id` = scan di riwayat, selain itu export seperti `find_export`
+    /// (nomor dari `results list`, nama file, atau path)
+    pub fn load(spec: &str) -> anyhow::Result<Self> {
+        let spec = spec.trim();
+
+        match spec.strip_prefix('#') {
+            Some(id) => Self::from_history(id.parse()?),
+            None => Self::from_file(&find_export(Some(spec))?),
+        }
+    }
+
+    fn from_history(id: i64) -> anyhow::Result<Self> {
+        let conn = history::open()?;
+        let (meta, results) = history::load_scan(&conn, id)?;
+        Ok(Self {
+            label: format!("history #{}", id),
+            meta: Some(meta),
+            results,
+            partial: false,
+        })
+    }
+
+    fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let (meta, results) = load_results(path)?;
+        let partial = matches!(ExportFormat::from_path(path), Some(ExportFormat::Txt))
+            && !fs::read_to_string(path)?.contains(NOT_WORKING_HEADER);
+        Ok(Self {
+            label: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+            meta,
+            results,
+            partial,
+        })
+    }
+}
+
+/// Baca hasil export (metadata hanya ada di json dan txt)
+pub fn load_results(path: &Path) -> anyhow::Result<(Option<ScanMeta>, Vec<ScanResult>)> {
+    let content = fs::read_to_string(path)?;
+
+    match ExportFormat::from_path(path) {
+        Some(ExportFormat::Json) => {
+            let report: ScanReport = serde_json::from_str(&content)?;
+            Ok((Some(report.meta), report.results))
+        }
+        Some(ExportFormat::Ndjson) => {
+            let results = content
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(serde_json::from_str)
+                .collect::<Result<Vec<ScanResult>, _>>()?;
+            Ok((None, results))
+        }
+        Some(ExportFormat::Csv) => {
+            let mut reader = csv::Reader::from_reader(content.as_bytes());
+            let results = reader
+                .deserialize::<CsvRow>()
+                .map(|row| row.map(ScanResult::from))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok((None, results))
+        }
+        Some(ExportFormat::Txt) => Ok(parse_text(&content)),
+        None => Err(anyhow::anyhow!("Format tidak didukung: {}", path.display())),
+    }
+}
+
+/// Header bagian host gagal di laporan txt; laporan lama tanpa bagian ini tidak lengkap
+const NOT_WORKING_HEADER: &str = "NOT WORKING (";
+
+// Laporan txt: host working, non-Cloudflare dan (sejak ada NOT_WORKING_HEADER) host gagal.
+// Server, CF-Ray, body size dan latency tidak dibaca ulang
+fn parse_text(content: &str) -> (Option<ScanMeta>, Vec<ScanResult>) {
+    let mut meta = ScanMeta::new("", "", "", 0);
+    meta.tool_version.clear();
+    meta.resolver.clear();
+    meta.timestamp.clear();
+    let mut results: Vec<ScanResult> = Vec::new();
+
+    for line in content.lines() {
+        if let Some(version) = line.strip_prefix("InjectTools v").and_then(|l| l.split_whitespace().next()) {
+            meta.tool_version = version.to_string();
+        } else if let Some(value) = line.strip_prefix("Target: ") {
+            meta.target = value.to_string();
+        } else if let Some(value) = line.strip_prefix("Domain: ") {
+            meta.domain = value.to_string();
+        } else if let Some(value) = line.strip_prefix("Source: ") {
+            meta.source = value.to_string();
+        } else if let Some(value) = line.strip_prefix("Timeout: ") {
+            meta.timeout = value.trim_end_matches('s').parse().unwrap_or(0);
+        } else if let Some(value) = line.strip_prefix("Resolver: ") {
+            meta.resolver = value.to_string();
+        } else if let Some(value) = line.strip_prefix("Timestamp: ") {
+            meta.timestamp = value.to_string();
+        }
+
+        let (is_working, rest) = if let Some(rest) = line.strip_prefix("✓ ") {
+            (true, rest)
+        } else if let Some(rest) = line.strip_prefix("• ").or_else(|| line.strip_prefix("✗ ")) {
+            (false, rest)
+        } else {
+            continue;
+        };
+
+        let fields: Vec<&str> = rest.split(" | ").collect();
+        let [subdomain, ip, provider, status, ..] = fields[..] else {
+            continue;
+        };
+        // Host working juga muncul lagi di bagian non-Cloudflare
+        if results.iter().any(|r| r.subdomain == subdomain) {
+            continue;
+        }
+
+        let status_code = status.strip_prefix("Status: ").and_then(|s| s.parse().ok()).filter(|&s| s != 0);
+        let provider = provider.parse().ok();
+        let error_msg = fields.iter().find_map(|f| f.strip_prefix("Error: ")).map(str::to_string);
+        let cname_chain = fields
+            .iter()
+            .find_map(|f| f.strip_prefix("CNAME: "))
+            .map(|chain| split_cnames(chain.split(" (").next().unwrap_or_default()))
+            .unwrap_or_default();
+        results.push(ScanResult {
+            subdomain: subdomain.to_string(),
+            ip: ip.to_string(),
+            port: None,
+            provider,
+            cname_provider: cdn::classify_cnames(&cname_chain),
+            cname_chain,
+            wildcard: fields
+                .iter()
+                .find_map(|f| f.strip_prefix("Wildcard: *."))
+                .map(str::to_string),
+            is_working,
+            status_code,
+            server: None,
+            cf_ray: None,
+            body_size: None,
+            latency_ms: None,
+            error_msg: error_msg.clone(),
+            retryable: false,
+            // IP kosong = DNS gagal / wildcard yang tidak di-probe
+            ip_results: if ip.is_empty() {
+                Vec::new()
+            } else {
+                vec![IpResult {
+                    ip: ip.to_string(),
+                    provider,
+                    is_working,
+                    status_code,
+                    latency_ms: None,
+                    error_msg,
+                }]
+            },
+        });
+    }
+
+    let has_meta = !meta.domain.is_empty() || !meta.target.is_empty();
+    (has_meta.then_some(meta), results)
+}
+
+// " | CNAME: a > b (Provider)", kosong kalau tidak ada CNAME
+fn cname_field(result: &ScanResult) -> String {
+    if result.cname_chain.is_empty() {
+        return String::new();
+    }
+    match result.cname_provider {
+        Some(provider) => format!(" | CNAME: {} ({})", result.cname_chain.join(" > "), provider),
+        None => format!(" | CNAME: {}", result.cname_chain.join(" > ")),
+    }
+}
+
+fn split_cnames(chain: &str) -> Vec<String> {
+    chain.split(" > ").filter(|c| !c.is_empty()).map(str::to_string).collect()
+}
+
+fn render_text(meta: Option<&ScanMeta>, results: &[ScanResult]) -> String {
     let mut content = String::new();
-    content.push_str(&format!("InjectTools v2.3 - Scan Results\n"));
-    content.push_str(&format!("Domain: {}\n", domain));
-    content.push_str(&format!("Timestamp: {}\n", Local::now().format("%Y-%m-%d %H:%M:%S")));
+
+    match meta {
+        Some(meta) => {
+            content.push_str(&format!("InjectTools v{} - Scan Results\n", meta.tool_version));
+            content.push_str(&format!("Target: {}\n", meta.target));
+            content.push_str(&format!("Domain: {}\n", meta.domain));
+            content.push_str(&format!("Source: {}\n", meta.source));
+            content.push_str(&format!("Timeout: {}s\n", meta.timeout));
+            content.push_str(&format!("Resolver: {}\n", meta.resolver));
+            content.push_str(&format!("Timestamp: {}\n", meta.timestamp));
+        }
+        None => content.push_str("InjectTools - Scan Results\n"),
+    }
     content.push_str(&format!("\n{}\n\n", "=".repeat(60)));
-    
+
     let working: Vec<_> = results.iter().filter(|r| r.is_working).collect();
-    let non_cf: Vec<_> = results.iter().filter(|r| !r.is_cloudflare && r.status_code.is_some()).collect();
-    
+    let non_cf: Vec<_> = results
+        .iter()
+        .filter(|r| r.provider != Some(Provider::Cloudflare) && r.status_code.is_some())
+        .collect();
+
     content.push_str(&format!("WORKING BUGS ({}):\n", working.len()));
     content.push_str(&format!("{}\n\n", "-".repeat(60)));
-    
+
     if working.is_empty() {
         content.push_str("No working bugs found\n");
     } else {
         for result in &working {
             content.push_str(&format!(
-                "✓ {} | {} | Status: {}\n",
+                "✓ {} | {} | {} | Status: {}",
                 result.subdomain,
                 result.ip,
+                cdn::provider_name(result.provider),
                 result.status_code.unwrap_or(0)
             ));
+            content.push_str(&cname_field(result));
+            if let Some(parent) = &result.wildcard {
+                content.push_str(&format!(" | Wildcard: *.{}", parent));
+            }
+            if let Some(server) = &result.server {
+                content.push_str(&format!(" | Server: {}", server));
+            }
+            if let Some(cf_ray) = &result.cf_ray {
+                content.push_str(&format!(" | CF-Ray: {}", cf_ray));
+            }
+            if let Some(body_size) = result.body_size {
+                content.push_str(&format!(" | Body: {} bytes", body_size));
+            }
+            if let Some(latency) = result.latency_ms {
+                content.push_str(&format!(" | Latency: {} ms", latency));
+            }
+            if let Some(error) = &result.error_msg {
+                content.push_str(&format!(" | {}", error));
+            }
+            content.push('\n');
+
+            // Breakdown per IP (mode --all-ips)
+            if result.ip_results.len() > 1 {
+                for ip_result in &result.ip_results {
+                    content.push_str(&format!(
+                        "    {} {} | {} | {}\n",
+                        if ip_result.is_working { "✓" } else { "✗" },
+                        ip_result.ip,
+                        cdn::provider_name(ip_result.provider),
+                        ip_result
+                            .error_msg
+                            .clone()
+                            .unwrap_or_else(|| format!("Status: {}", ip_result.status_code.unwrap_or(0)))
+                    ));
+                }
+            }
         }
     }
-    
+
     content.push_str(&format!("\n\nNON-CLOUDFLARE RESPONSES ({}):\n", non_cf.len()));
     content.push_str(&format!("{}\n\n", "-".repeat(60)));
-    
+
     for result in &non_cf {
         content.push_str(&format!(
-            "• {} | {} | Status: {}\n",
+            "• {} | {} | {} | Status: {}{}\n",
             result.subdomain,
             result.ip,
-            result.status_code.unwrap_or(0)
+            cdn::provider_name(result.provider),
+            result.status_code.unwrap_or(0),
+            cname_field(result)
         ));
     }
-    
-    content.push_str(&format!("\n\nSTATISTICS:\n"));
+
+    // Semua host gagal ikut ditulis supaya diff / retest dari txt tetap lengkap
+    let failed: Vec<_> = results.iter().filter(|r| !r.is_working).collect();
+    content.push_str(&format!("\n\n{}{}):\n", NOT_WORKING_HEADER, failed.len()));
+    content.push_str(&format!("{}\n\n", "-".repeat(60)));
+
+    for result in &failed {
+        content.push_str(&format!(
+            "✗ {} | {} | {} | Status: {}{}",
+            result.subdomain,
+            result.ip,
+            cdn::provider_name(result.provider),
+            result.status_code.unwrap_or(0),
+            cname_field(result)
+        ));
+        if let Some(parent) = &result.wildcard {
+            content.push_str(&format!(" | Wildcard: *.{}", parent));
+        }
+        if let Some(error) = &result.error_msg {
+            content.push_str(&format!(" | Error: {}", error));
+        }
+        content.push('\n');
+    }
+
+    content.push_str("\n\nSTATISTICS:\n");
     content.push_str(&format!("{}\n", "-".repeat(60)));
     content.push_str(&format!("Total Scanned: {}\n", results.len()));
     content.push_str(&format!("Working Bugs: {}\n", working.len()));
     content.push_str(&format!("Non-CF: {}\n", non_cf.len()));
-    
-    fs::write(&filepath, content)?;
-    
-    println!("\n{}", "═".repeat(60).green());
-    println!("{}", "📁 RESULTS EXPORTED".green().bold());
-    println!("{}", "═".repeat(60).green());
-    println!("\n{} {}", "File:".bright_black(), filename.green());
-    println!("{} {}", "Path:".bright_black(), filepath.display().to_string().bright_black());
-    println!("{} {} bugs\n", "Working:".bright_black(), working.len().to_string().green());
-    
-    Ok(())
+    content.push_str(&format!("Not Working: {}\n", failed.len()));
+
+    content
 }
 
-pub fn view_results() -> anyhow::Result<()> {
+/// Semua file export (semua format), terbaru dulu
+pub fn list_exports() -> anyhow::Result<Vec<PathBuf>> {
     let results_dir = Config::results_dir();
-    
     if !results_dir.exists() {
-        println!("\n{}", "📂 No results directory found".yellow());
-        return Ok(());
+        return Ok(Vec::new());
     }
-    
-    let entries = fs::read_dir(&results_dir)?;
-    let mut files: Vec<_> = entries
+
+    let mut files: Vec<_> = fs::read_dir(&results_dir)?
         .filter_map(|e| e.ok())
-        .filter(|e| {
-            e.path()
-                .extension()
-                .and_then(|s| s.to_str())
-                .map(|s| s == "txt")
-                .unwrap_or(false)
-        })
+        .map(|e| e.path())
+        .filter(|path| ExportFormat::from_path(path).is_some())
         .collect();
-    
-    if files.is_empty() {
-        println!("\n{}", "📂 No scan results found".yellow());
-        return Ok(());
-    }
-    
-    files.sort_by_key(|e| {
-        e.metadata()
+
+    files.sort_by_key(|path| {
+        fs::metadata(path)
             .and_then(|m| m.modified())
             .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
     });
     files.reverse();
-    
+
+    Ok(files)
+}
+
+/// Tampilkan daftar export terbaru (`limit` = None untuk semua), return semua file
+pub fn print_exports(limit: Option<usize>) -> anyhow::Result<Vec<PathBuf>> {
+    let results_dir = Config::results_dir();
+
+    if !results_dir.exists() {
+        outln!("\n{}", "📂 No results directory found".yellow());
+        return Ok(Vec::new());
+    }
+
+    let files = list_exports()?;
+
+    if files.is_empty() {
+        outln!("\n{}", "📂 No scan results found".yellow());
+        return Ok(files);
+    }
+
     crate::ui::print_header("EXPORTED RESULTS");
-    println!("\n{} {} files\n", "Total:".bright_black(), files.len().to_string().yellow());
-    
-    for (idx, entry) in files.iter().enumerate().take(10) {
-        let filename = entry.file_name();
-        let metadata = entry.metadata()?;
-        let modified = metadata.modified()?;
+    outln!("\n{} {} files\n", "Total:".bright_black(), files.len().to_string().yellow());
+
+    let shown = limit.unwrap_or(files.len());
+    for (idx, path) in files.iter().enumerate().take(shown) {
+        let modified = fs::metadata(path)?.modified()?;
         let datetime: chrono::DateTime<chrono::Local> = modified.into();
-        
-        println!(
+
+        outln!(
             "{}. {} {}",
             (idx + 1).to_string().cyan(),
-            filename.to_string_lossy().green(),
+            path.file_name().unwrap_or_default().to_string_lossy().green(),
             format!("({})", datetime.format("%Y-%m-%d %H:%M")).bright_black()
         );
     }
-    
-    if files.len() > 10 {
-        println!("\n... and {} more files", files.len() - 10);
+
+    if files.len() > shown {
+        outln!("\n... and {} more files", files.len() - shown);
     }
-    
-    println!("\n{} {}", "Directory:".bright_black(), results_dir.display().to_string().bright_black());
-    
-    // Offer to view latest
-    println!("\n{}", "View latest result? (y/n)".bold());
-    let choice = crate::ui::read_line();
-    
-    if choice.trim().eq_ignore_ascii_case("y") {
-        let latest = &files[0];
-        let content = fs::read_to_string(latest.path())?;
-        
-        println!("\n{}", "═".repeat(60).cyan());
-        println!("{}", content);
-        println!("{}", "═".repeat(60).cyan());
-    }
-    
+
+    outln!("\n{} {}", "Directory:".bright_black(), results_dir.display().to_string().bright_black());
+
+    Ok(files)
+}
+
+/// File export dari nomor urut (1 = terbaru), nama file, atau path; None = terbaru
+pub fn find_export(spec: Option<&str>) -> anyhow::Result<PathBuf> {
+    let files = list_exports()?;
+
+    let Some(spec) = spec else {
+        return files.into_iter().next().ok_or_else(|| anyhow::anyhow!("Belum ada hasil export"));
+    };
+
+    if let Ok(index) = spec.parse::<usize>() {
+        return index
+            .checked_sub(1)
+            .and_then(|i| files.get(i).cloned())
+            .ok_or_else(|| anyhow::anyhow!("Tidak ada export nomor {}", spec));
+    }
+
+    let path = Path::new(spec);
+    let path = if path.exists() { path.to_path_buf() } else { Config::results_dir().join(spec) };
+    if path.exists() {
+        Ok(path)
+    } else {
+        Err(anyhow::anyhow!("File export tidak ditemukan: {}", spec))
+    }
+}
+
+/// Tampilkan isi satu export (json/csv/ndjson dirender seperti laporan txt)
+pub fn show_export(path: &Path) -> anyhow::Result<()> {
+    let content = match ExportFormat::from_path(path) {
+        Some(ExportFormat::Txt) => fs::read_to_string(path)?,
+        _ => {
+            let (meta, results) = load_results(path)?;
+            render_text(meta.as_ref(), &results)
+        }
+    };
+
+    outln!("\n{}", "═".repeat(60).cyan());
+    outln!("{}", content);
+    outln!("{}", "═".repeat(60).cyan());
+
     Ok(())
-}
\ No newline at end of file
+}
+
+pub fn view_results() -> anyhow::Result<()> {
+    let files = print_exports(Some(10))?;
+    if files.is_empty() {
+        return Ok(());
+    }
+
+    // Pilih file yang mau dilihat (enter = terbaru)
+    outln!("\n{}", "Lihat file nomor berapa? (enter = terbaru, n = batal)".bold());
+    let choice = crate::ui::read_line();
+    let choice = choice.trim();
+
+    if choice.eq_ignore_ascii_case("n") {
+        return Ok(());
+    }
+
+    let index: usize = if choice.is_empty() { 1 } else { choice.parse().unwrap_or(0) };
+    let Some(path) = index.checked_sub(1).and_then(|i| files.get(i)) else {
+        outln!("\n{}", "❌ Pilihan tidak valid".red());
+        return Ok(());
+    };
+
+    show_export(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(subdomain: &str, ip: &str, working: bool, error: Option<&str>) -> ScanResult {
+        let mut result = ScanResult::dns_failed(subdomain, String::new());
+        result.ip = ip.to_string();
+        result.provider = Some(Provider::Cloudflare);
+        result.is_working = working;
+        result.status_code = working.then_some(200);
+        result.error_msg = error.map(str::to_string);
+        if !ip.is_empty() {
+            result.ip_results = vec![IpResult {
+                ip: ip.to_string(),
+                provider: result.provider,
+                is_working: working,
+                status_code: result.status_code,
+                latency_ms: None,
+                error_msg: result.error_msg.clone(),
+            }];
+        }
+        result
+    }
+
+    #[test]
+    fn text_report_keeps_failed_hosts() {
+        let results = vec![
+            row("ok.example.com", "104.16.1.1", true, None),
+            row("cf.example.com", "104.16.2.2", false, Some("HTTP 403")),
+            ScanResult::dns_failed("gone.example.com", "NXDOMAIN".to_string()),
+        ];
+        let content = render_text(None, &results);
+        assert!(content.contains(NOT_WORKING_HEADER));
+
+        let (_, parsed) = parse_text(&content);
+        let hosts: Vec<(&str, bool)> = parsed.iter().map(|r| (r.subdomain.as_str(), r.is_working)).collect();
+        assert_eq!(
+            hosts,
+            vec![("ok.example.com", true), ("cf.example.com", false), ("gone.example.com", false)]
+        );
+        assert_eq!(parsed[1].error_msg.as_deref(), Some("HTTP 403"));
+        assert!(parsed[2].ip_results.is_empty());
+    }
+}
@@ -1,29 +1,279 @@
 use colored::Colorize;
+use futures::stream::{self, StreamExt};
 use indicatif::{ProgressBar, ProgressStyle};
+use std::collections::HashSet;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use std::net::{TcpStream, ToSocketAddrs};
+use std::net::IpAddr;
 use std::process::Command;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+use serde::{Deserialize, Serialize};
 
-use crate::dns;
+use crate::{out, outln};
+use crate::cdn::{self, Provider};
+use crate::dns::{self, IpFamily};
+use crate::probe::{self, HttpResponse, TlsOutcome, TlsProbe};
+use crate::results;
 use crate::ui;
+use crate::wildcard::WildcardMap;
 
+/// Cara menentukan apakah bug host working
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProbeMode {
+    /// TLS handshake saja (SNI = target)
+    Tls,
+    /// TLS handshake + HTTP request lewat tunnel SNI
+    Http,
+    /// TLS handshake + WebSocket upgrade (VMess/VLESS/SSH over WS)
+    Ws,
+}
+
+/// Pengaturan scan
 #[derive(Debug, Clone)]
+pub struct ScanOptions {
+    /// Timeout per host (detik), berlaku untuk DNS, TLS handshake dan HTTP
+    pub timeout: u64,
+    /// Jumlah host yang dites bersamaan
+    pub concurrency: usize,
+    /// Batas global request per detik (None = unlimited)
+    pub rate_limit: Option<f64>,
+    pub mode: ProbeMode,
+    /// SNI untuk TLS handshake (None = target)
+    pub sni: Option<String>,
+    /// Host header untuk HTTP/WS request (None = target)
+    pub host_header: Option<String>,
+    /// Path untuk HTTP/WS request
+    pub path: String,
+    /// Port yang dicoba berurutan, berhenti di port pertama yang working
+    pub ports: Vec<u16>,
+    /// Jeda sebelum tiap host (ms), anti rate-limit
+    pub delay_ms: u64,
+    /// Ulangi host yang gagal karena error (timeout, reset, DNS)
+    pub retries: u32,
+    /// false = koneksi plain TCP (http:// / ws://)
+    pub tls: bool,
+    /// Probe semua IP hasil DNS, bukan hanya yang pertama
+    pub all_ips: bool,
+    pub ip_family: IpFamily,
+    /// DNS resolver + cache bersama untuk seluruh scan
+    pub resolver: Arc<dns::Resolver>,
+    /// Batch: deteksi wildcard DNS dan collapse kandidat yang hanya cocok wildcard
+    pub wildcard_check: bool,
+}
+
+impl ScanOptions {
+    pub fn sni<'a>(&'a self, target: &'a str) -> &'a str {
+        self.sni.as_deref().unwrap_or(target)
+    }
+
+    pub fn host<'a>(&'a self, target: &'a str) -> &'a str {
+        self.host_header.as_deref().unwrap_or(target)
+    }
+
+    /// Daftar port probe, minimal satu (443)
+    pub fn probe_ports(&self) -> &[u16] {
+        if self.ports.is_empty() {
+            &[443]
+        } else {
+            &self.ports
+        }
+    }
+
+    /// Port utama (pertama di daftar)
+    pub fn port(&self) -> u16 {
+        self.probe_ports()[0]
+    }
+
+    fn protocol(&self) -> &'static str {
+        match (self.mode, self.tls) {
+            (ProbeMode::Ws, true) => "WSS",
+            (ProbeMode::Ws, false) => "WS",
+            (_, true) => "HTTPS",
+            (_, false) => "HTTP",
+        }
+    }
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            timeout: 10,
+            concurrency: 20,
+            rate_limit: None,
+            mode: ProbeMode::Http,
+            sni: None,
+            host_header: None,
+            path: "/".to_string(),
+            ports: vec![443],
+            delay_ms: 0,
+            retries: 0,
+            tls: true,
+            all_ips: false,
+            ip_family: IpFamily::Any,
+            resolver: dns::Resolver::shared(),
+            wildcard_check: true,
+        }
+    }
+}
+
+// Global rate limiter: tiap acquire() mengambil slot waktu berikutnya
+pub(crate) struct RateLimiter {
+    interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(rps: f64) -> Option<Self> {
+        // NaN / rps super kecil (interval di luar Duration) = tanpa limiter
+        if rps.is_nan() || rps <= 0.0 {
+            return None;
+        }
+        Some(Self {
+            interval: Duration::try_from_secs_f64(1.0 / rps).ok()?,
+            next_slot: Mutex::new(Instant::now()),
+        })
+    }
+
+    pub(crate) async fn acquire(&self) {
+        let mut next_slot = self.next_slot.lock().await;
+        let now = Instant::now();
+        if *next_slot > now {
+            tokio::time::sleep_until(*next_slot).await;
+        }
+        *next_slot = (*next_slot).max(now) + self.interval;
+    }
+}
+
+/// Hasil probe untuk satu IP dari subdomain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpResult {
+    pub ip: String,
+    /// CDN yang mem-fronting IP (None = bukan CDN yang dikenal)
+    pub provider: Option<Provider>,
+    pub is_working: bool,
+    pub status_code: Option<u16>,
+    pub latency_ms: Option<u64>,
+    pub error_msg: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScanResult {
     pub subdomain: String,
+    /// IP yang dilaporkan: IP working pertama, atau IP pertama kalau semua gagal
     pub ip: String,
-    pub is_cloudflare: bool,
+    /// Port probe terakhir (port working kalau ada), None = DNS gagal / tidak tercatat
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// CDN yang mem-fronting IP (None = bukan CDN yang dikenal)
+    pub provider: Option<Provider>,
+    /// Rantai CNAME dari DNS, kosong = A/AAAA langsung
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cname_chain: Vec<String>,
+    /// CDN menurut suffix CNAME (bisa ada walau IP tidak dikenal)
+    #[serde(default)]
+    pub cname_provider: Option<Provider>,
+    /// Parent domain kalau host hanya cocok dengan wildcard DNS (*.parent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wildcard: Option<String>,
     pub is_working: bool,
     pub status_code: Option<u16>,
+    pub server: Option<String>,
+    pub cf_ray: Option<String>,
+    pub body_size: Option<usize>,
+    pub latency_ms: Option<u64>,
     pub error_msg: Option<String>,
+    /// Breakdown per IP (kosong kalau DNS resolution gagal)
+    pub ip_results: Vec<IpResult>,
+    /// Gagal karena timeout / error koneksi, boleh di-retry (tidak di-export)
+    #[serde(skip)]
+    pub(crate) retryable: bool,
+}
+
+impl ScanResult {
+    pub(crate) fn dns_failed(subdomain: &str, error: String) -> Self {
+        Self {
+            subdomain: subdomain.to_string(),
+            ip: String::new(),
+            port: None,
+            provider: None,
+            cname_chain: Vec::new(),
+            cname_provider: None,
+            wildcard: None,
+            is_working: false,
+            status_code: None,
+            server: None,
+            cf_ray: None,
+            body_size: None,
+            latency_ms: None,
+            error_msg: Some(format!("DNS resolution failed: {}", error)),
+            ip_results: Vec::new(),
+            retryable: false,
+        }
+    }
+
+    /// Batas waktu per host habis sebelum probe selesai
+    pub(crate) fn timed_out(subdomain: &str, ips: &[String], port: u16) -> Self {
+        let ip_results: Vec<IpResult> = ips
+            .iter()
+            .map(|ip| IpResult {
+                ip: ip.clone(),
+                provider: cdn::lookup(ip),
+                is_working: false,
+                status_code: None,
+                latency_ms: None,
+                error_msg: Some("timeout".to_string()),
+            })
+            .collect();
+        Self {
+            ip: ips.first().cloned().unwrap_or_default(),
+            port: Some(port),
+            provider: ip_results.first().and_then(|r| r.provider),
+            error_msg: Some("timeout".to_string()),
+            ip_results,
+            retryable: true,
+            ..Self::dns_failed(subdomain, String::new())
+        }
+    }
+
+    /// Host yang di-collapse ke wildcard *.parent: dicatat tanpa probe
+    pub(crate) fn wildcard_collapsed(subdomain: &str, parent: &str) -> Self {
+        Self {
+            wildcard: Some(parent.to_string()),
+            error_msg: Some(format!("Wildcard DNS (*.{}), tidak dites", parent)),
+            ..Self::dns_failed(subdomain, String::new())
+        }
+    }
+
+    /// True untuk baris hasil collapse wildcard (tidak pernah di-probe)
+    pub fn is_wildcard_collapsed(&self) -> bool {
+        self.wildcard.is_some() && self.ip_results.is_empty()
+    }
+
+    /// Catat rantai CNAME beserta provider-nya
+    pub(crate) fn with_cnames(mut self, cnames: &[String]) -> Self {
+        self.cname_provider = cdn::classify_cnames(cnames);
+        self.cname_chain = cnames.to_vec();
+        self
+    }
+
+    pub fn working_ips(&self) -> Vec<&str> {
+        self.ip_results
+            .iter()
+            .filter(|r| r.is_working)
+            .map(|r| r.ip.as_str())
+            .collect()
+    }
 }
 
 // TCP latency check with timing
 fn tcp_latency_check(host: &str, port: u16, timeout_secs: u64) -> Option<u128> {
-    let addr = format!("{}:{}", host, port);
-    
-    let socket_addrs: Vec<_> = match addr.to_socket_addrs() {
+    // (host, port) juga menerima IPv6 literal tanpa bracket
+    let socket_addrs: Vec<_> = match (host, port).to_socket_addrs() {
         Ok(addrs) => addrs.collect(),
         Err(_) => return None,
     };
@@ -72,266 +322,803 @@ fn ping_test(host: &str, timeout_secs: u64) -> Option<u128> {
     None
 }
 
-// Test SSL/TLS connection using openssl s_client
-// Returns true if SSL handshake successful
-fn test_ssl_connection(ip: &str, port: u16, servername: &str, timeout_secs: u64) -> bool {
-    let connect_addr = format!("{}:{}", ip, port);
-    
-    // openssl s_client -connect <ip>:443 -servername <hostname> -brief < /dev/null
-    let output = Command::new("timeout")
-        .arg(timeout_secs.to_string())
-        .arg("openssl")
-        .arg("s_client")
-        .arg("-connect")
-        .arg(&connect_addr)
-        .arg("-servername")
-        .arg(servername)
-        .arg("-brief")
-        .stdin(std::process::Stdio::null())
-        .output();
-    
-    if let Ok(result) = output {
-        if result.status.success() {
-            // Parse output untuk verify SSL handshake
-            if let Ok(stdout) = String::from_utf8(result.stdout) {
-                // Check for successful handshake indicators
-                if stdout.contains("CONNECTION ESTABLISHED") 
-                    || stdout.contains("Verification: OK")
-                    || stdout.contains("Cipher:")
-                    || result.status.code() == Some(0) {
-                    return true;
-                }
-            }
-            
-            // stderr juga bisa contain success indicators
-            if let Ok(stderr) = String::from_utf8(result.stderr) {
-                if stderr.contains("Verify return code: 0")
-                    || stderr.contains("SSL handshake has read")
-                    || !stderr.contains("error") {
-                    return true;
-                }
+// Resolve alamat IP dari string hasil DNS
+fn parse_ip(ip: &str) -> Option<IpAddr> {
+    ip.parse::<IpAddr>().ok()
+}
+
+// Test SSL/TLS handshake secara native (rustls), connect ke IP dengan SNI servername
+async fn test_ssl_connection(ip: &str, port: u16, servername: &str, timeout_secs: u64) -> TlsProbe {
+    match parse_ip(ip) {
+        Some(addr) => probe::tls_probe(addr, port, servername, Duration::from_secs(timeout_secs)).await,
+        None => TlsProbe {
+            outcome: TlsOutcome::Failed(format!("IP tidak valid: {}", ip)),
+            latency_ms: None,
+        },
+    }
+}
+
+// Hasil probe satu IP subdomain
+struct ProbeReport {
+    mode: ProbeMode,
+    tls: TlsProbe,
+    http: Option<Result<HttpResponse, String>>,
+}
+
+impl ProbeReport {
+    fn failed(mode: ProbeMode, outcome: TlsOutcome) -> Self {
+        Self {
+            mode,
+            tls: TlsProbe {
+                outcome,
+                latency_ms: None,
+            },
+            http: None,
+        }
+    }
+
+    fn is_working(&self, provider: Option<Provider>) -> bool {
+        if !self.tls.outcome.is_ok() {
+            return false;
+        }
+        match (&self.http, self.mode) {
+            // Hanya 101 Switching Protocols yang dihitung working
+            (Some(Ok(response)), ProbeMode::Ws) => response.status_code == 101,
+            (Some(Ok(response)), _) => response.routes_to_target(),
+            (Some(Err(_)), _) => false,
+            // Mode TLS saja: CDN + SSL success = working
+            (None, _) => provider.is_some(),
+        }
+    }
+
+    fn error_msg(&self) -> Option<String> {
+        if !self.tls.outcome.is_ok() {
+            return Some(self.tls.outcome.to_string());
+        }
+        match (&self.http, self.mode) {
+            (Some(Err(e)), _) => Some(e.clone()),
+            (Some(Ok(response)), ProbeMode::Ws) if response.status_code != 101 => Some(format!(
+                "WebSocket upgrade ditolak (HTTP {})",
+                response.status_code
+            )),
+            (Some(Ok(response)), ProbeMode::Http) if !response.routes_to_target() => {
+                Some(format!("HTTP {} (ditolak edge)", response.status_code))
             }
-            
-            // Exit code 0 = success
-            return true;
+            _ => None,
         }
     }
-    
-    false
+
+    // Timeout / error koneksi / error baca setelah handshake; cert mismatch,
+    // TLS alert dan status HTTP (403, WS 400) tidak berubah kalau diulang
+    fn is_retryable(&self) -> bool {
+        if self.tls.outcome.is_ok() {
+            matches!(self.http, Some(Err(_)))
+        } else {
+            self.tls.outcome.is_transient()
+        }
+    }
+
+    fn response(&self) -> Option<&HttpResponse> {
+        self.http.as_ref().and_then(|r| r.as_ref().ok())
+    }
 }
 
-pub async fn test_target(target: &str, timeout: u64) -> anyhow::Result<()> {
-    println!("\n{}", "Testing target host...".cyan());
-    println!("{}", "━".repeat(50).bright_black());
+// Kirim request sesuai probe mode lewat stream yang sudah terhubung
+async fn send_request<S>(
+    stream: &mut S,
+    target: &str,
+    options: &ScanOptions,
+    timeout: Duration,
+) -> Option<Result<HttpResponse, String>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let host = options.host(target);
+    match options.mode {
+        ProbeMode::Tls => None,
+        ProbeMode::Http => Some(probe::http_probe(stream, host, &options.path, timeout).await),
+        ProbeMode::Ws => Some(probe::ws_probe(stream, host, &options.path, timeout).await),
+    }
+}
+
+// Connect ke IP subdomain dengan SNI target, lalu (mode HTTP/WS) kirim request
+// dengan Host header target supaya kelihatan apakah edge meneruskan ke tunnel
+async fn probe_ip(ip: &str, port: u16, target: &str, options: &ScanOptions) -> ProbeReport {
+    let timeout = Duration::from_secs(options.timeout);
+    let addr = match parse_ip(ip) {
+        Some(addr) => addr,
+        None => {
+            return ProbeReport::failed(
+                options.mode,
+                TlsOutcome::Failed(format!("IP tidak valid: {}", ip)),
+            )
+        }
+    };
+
+    if options.tls {
+        match probe::tls_connect(addr, port, options.sni(target), timeout).await {
+            Ok((mut stream, latency)) => ProbeReport {
+                mode: options.mode,
+                tls: TlsProbe {
+                    outcome: TlsOutcome::HandshakeOk,
+                    latency_ms: Some(latency),
+                },
+                http: send_request(&mut stream, target, options, timeout).await,
+            },
+            Err(outcome) => ProbeReport::failed(options.mode, outcome),
+        }
+    } else {
+        match probe::tcp_connect(addr, port, timeout).await {
+            Ok((mut stream, latency)) => ProbeReport {
+                mode: options.mode,
+                tls: TlsProbe {
+                    outcome: TlsOutcome::HandshakeOk,
+                    latency_ms: Some(latency),
+                },
+                http: send_request(&mut stream, target, options, timeout).await,
+            },
+            Err(outcome) => ProbeReport::failed(options.mode, outcome),
+        }
+    }
+}
+
+/// Cek target online (DNS, ping, TLS, lalu TCP)
+pub async fn test_target(target: &str, timeout: u64, resolver: &dns::Resolver) -> anyhow::Result<bool> {
+    outln!("\n{}", "Testing target host...".cyan());
+    outln!("{}", "━".repeat(50).bright_black());
     
     // Step 1: DNS Resolution Test
-    let _resolved_ip = match dns::resolve_domain_first(target).await {
+    let resolved_ip = match resolver.resolve_first(target).await {
         Ok(ip) => ip,
         Err(_) => {
-            println!("\n{}", "❌ TARGET OFFLINE".red().bold());
-            println!("{}", "Reason: DNS resolution failed".bright_black());
-            return Ok(());
+            outln!("\n{}", "❌ TARGET OFFLINE".red().bold());
+            outln!("{}", "Reason: DNS resolution failed".bright_black());
+            return Ok(false);
         }
     };
     
     // Step 2: Ping Test
-    if let Some(_ping_time) = ping_test(target, 3) {
-        println!("\n{}", "✅ TARGET ONLINE".green().bold());
-        return Ok(());
+    if let Some(_ping_time) = ping_test(target, timeout) {
+        outln!("\n{}", "✅ TARGET ONLINE".green().bold());
+        return Ok(true);
     }
     
     // Step 3: SSL/TLS Test (HTTPS port 443)
-    if test_ssl_connection(target, 443, target, timeout) {
-        println!("\n{}", "✅ TARGET ONLINE".green().bold());
-        return Ok(());
+    if test_ssl_connection(&resolved_ip, 443, target, timeout).await.outcome.is_ok() {
+        outln!("\n{}", "✅ TARGET ONLINE".green().bold());
+        return Ok(true);
     }
     
     // Step 4: TCP Port Check (last resort)
     let tcp_ports = vec![443, 80, 8080];
     
     for port in &tcp_ports {
-        if tcp_latency_check(target, *port, 3).is_some() {
-            println!("\n{}", "✅ TARGET ONLINE".green().bold());
-            return Ok(());
+        if tcp_latency_check(target, *port, timeout).is_some() {
+            outln!("\n{}", "✅ TARGET ONLINE".green().bold());
+            return Ok(true);
         }
     }
     
     // All checks failed
-    println!("\n{}", "❌ TARGET OFFLINE".red().bold());
-    println!("{}", "Reason: All connection attempts failed".bright_black());
-    Ok(())
+    outln!("\n{}", "❌ TARGET OFFLINE".red().bold());
+    outln!("{}", "Reason: All connection attempts failed".bright_black());
+    Ok(false)
 }
 
-pub async fn test_single(target: &str, subdomain: &str, timeout: u64) -> anyhow::Result<()> {
-    println!("\n{}", "Testing subdomain...".cyan());
-    println!("{}", "━".repeat(50).bright_black());
-    println!("\n{} {}", "Subdomain:".bright_black(), subdomain);
-    println!("{} {}", "Target:".bright_black(), target);
-    
-    // DNS resolution untuk dapat IP subdomain
-    let (ip, is_cf) = match dns::resolve_domain_first(subdomain).await {
-        Ok(ip) => {
-            let is_cf = dns::is_cloudflare_ip(&ip);
-            (ip, is_cf)
-        }
+// Resolve subdomain lalu probe IP-nya (semua IP kalau options.all_ips).
+// options.timeout berlaku untuk resolve + probe sekaligus, bukan per tahap
+async fn scan_subdomain(target: &str, subdomain: &str, options: &ScanOptions) -> ScanResult {
+    let deadline = Instant::now() + Duration::from_secs(options.timeout);
+    let answer = match resolve_subdomain(subdomain, options).await {
+        Ok(answer) => answer,
         Err(e) => {
-            println!("\n{}", "═".repeat(50).red());
-            println!("{}", "❌ DNS RESOLUTION FAILED".red().bold());
-            println!("\n{} {}", "Subdomain:".bright_black(), subdomain.red());
-            println!("{} {}", "Error:".bright_black(), e.to_string().red());
-            println!("{}", "═".repeat(50).red());
-            return Ok(());
+            return ScanResult {
+                retryable: e == DNS_TIMEOUT,
+                ..ScanResult::dns_failed(subdomain, e)
+            }
         }
     };
+    probe_until(deadline, target, subdomain, &answer.ips, options)
+        .await
+        .with_cnames(&answer.cnames)
+}
+
+/// probe_subdomain yang dihentikan di `deadline` (semua port + request sekaligus)
+pub(crate) async fn probe_until(
+    deadline: Instant,
+    target: &str,
+    subdomain: &str,
+    ips: &[String],
+    options: &ScanOptions,
+) -> ScanResult {
+    match tokio::time::timeout_at(deadline, probe_subdomain(target, subdomain, ips, options)).await {
+        Ok(result) => result,
+        Err(_) => ScanResult::timed_out(subdomain, ips, options.port()),
+    }
+}
+
+const DNS_TIMEOUT: &str = "timeout";
+
+/// IP subdomain yang akan diprobe (hanya yang pertama kecuali options.all_ips) + rantai CNAME
+pub(crate) async fn resolve_subdomain(subdomain: &str, options: &ScanOptions) -> Result<dns::Answer, String> {
+    let mut answer = match tokio::time::timeout(
+        Duration::from_secs(options.timeout),
+        options.resolver.resolve_answer(subdomain, options.ip_family),
+    )
+    .await
+    {
+        Ok(Ok(answer)) if !answer.ips.is_empty() => answer,
+        Ok(Ok(_)) => return Err("No IP found for domain".to_string()),
+        Ok(Err(e)) => return Err(e.to_string()),
+        Err(_) => return Err(DNS_TIMEOUT.to_string()),
+    };
+
+    if !options.all_ips {
+        answer.ips.truncate(1);
+    }
+    Ok(answer)
+}
+
+/// Probe IP subdomain yang sudah di-resolve ke satu target
+pub(crate) async fn probe_subdomain(
+    target: &str,
+    subdomain: &str,
+    ips: &[String],
+    options: &ScanOptions,
+) -> ScanResult {
+
+    // Connect ke tiap IP subdomain, SNI servername ke target.
+    // Port dicoba berurutan sampai ada IP yang working
+    let mut port = options.port();
+    let mut reports = Vec::new();
+    for &candidate in options.probe_ports() {
+        port = candidate;
+        reports = futures::future::join_all(ips.iter().map(|ip| probe_ip(ip, port, target, options))).await;
+        if ips.iter().zip(&reports).any(|(ip, report)| report.is_working(cdn::lookup(ip))) {
+            break;
+        }
+    }
+
+    let ip_results: Vec<IpResult> = ips
+        .iter()
+        .zip(&reports)
+        .map(|(ip, report)| {
+            let provider = cdn::lookup(ip);
+            IpResult {
+                ip: ip.clone(),
+                provider,
+                is_working: report.is_working(provider),
+                status_code: report.response().map(|r| r.status_code),
+                latency_ms: report.tls.latency_ms,
+                error_msg: report.error_msg(),
+            }
+        })
+        .collect();
+
+    let chosen = ip_results.iter().position(|r| r.is_working).unwrap_or(0);
+    let report = &reports[chosen];
+    let response = report.response();
+    let primary = &ip_results[chosen];
+
+    ScanResult {
+        subdomain: subdomain.to_string(),
+        ip: primary.ip.clone(),
+        port: Some(port),
+        provider: primary.provider,
+        cname_chain: Vec::new(),
+        cname_provider: None,
+        wildcard: None,
+        is_working: primary.is_working,
+        status_code: primary.status_code,
+        server: response.and_then(|r| r.server.clone()),
+        cf_ray: response.and_then(|r| r.cf_ray.clone()),
+        body_size: response.map(|r| r.body_size),
+        latency_ms: primary.latency_ms,
+        error_msg: primary.error_msg.clone(),
+        retryable: !primary.is_working && report.is_retryable(),
+        ip_results,
+    }
+}
+
+// scan_subdomain + retry
+async fn scan_host(target: &str, subdomain: &str, options: &ScanOptions) -> ScanResult {
+    with_retries(options, || scan_subdomain(target, subdomain, options)).await
+}
+
+/// Ulangi scan yang gagal karena timeout / error koneksi sampai options.retries kali
+pub(crate) async fn with_retries<F, Fut>(options: &ScanOptions, scan: F) -> ScanResult
+where
+    F: Fn() -> Fut,
+    Fut: std::future::Future<Output = ScanResult>,
+{
+    let mut result = scan().await;
+    for _ in 0..options.retries {
+        if result.is_working || !result.retryable {
+            break;
+        }
+        if options.delay_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(options.delay_ms)).await;
+        }
+        result = scan().await;
+    }
+    result
+}
+
+// Provider dari IP, plus rantai CNAME dan provider-nya kalau ada
+fn print_provider(result: &ScanResult) {
+    match result.provider {
+        Some(provider) => outln!("{} {}", "Provider:".bright_black(), provider.name().cyan()),
+        None => outln!("{} {}", "Provider:".bright_black(), cdn::provider_name(None).yellow()),
+    }
+    if !result.cname_chain.is_empty() {
+        outln!(
+            "{} {} {}",
+            "CNAME:".bright_black(),
+            result.cname_chain.join(" → ").cyan(),
+            result.cname_provider.map(|p| format!("({})", p.name())).unwrap_or_default().bright_black()
+        );
+    }
+}
+
+fn print_ip_breakdown(result: &ScanResult) {
+    if result.ip_results.len() < 2 {
+        return;
+    }
+
+    outln!("\n{}", "Per-IP Results:".bright_black());
+    for ip_result in &result.ip_results {
+        let provider = cdn::provider_name(ip_result.provider);
+        if ip_result.is_working {
+            outln!(
+                "  {} {} ({}) {}",
+                "🟢".green(),
+                ip_result.ip.green(),
+                provider,
+                ip_result.status_code.map(|c| format!("HTTP {}", c)).unwrap_or_default().bright_black()
+            );
+        } else {
+            outln!(
+                "  {} {} ({}) {}",
+                "🔴".red(),
+                ip_result.ip.dimmed(),
+                provider,
+                ip_result.error_msg.as_deref().unwrap_or("").bright_black()
+            );
+        }
+    }
+}
+
+pub async fn test_single(target: &str, subdomain: &str, options: &ScanOptions) -> anyhow::Result<ScanResult> {
+    outln!("\n{}", "Testing subdomain...".cyan());
+    outln!("{}", "━".repeat(50).bright_black());
+    outln!("\n{} {}", "Subdomain:".bright_black(), subdomain);
+    outln!("{} {}", "Target:".bright_black(), target);
     
-    print!("\n{} Testing SSL/TLS handshake...", "🔌".cyan());
+    out!("\n{} Testing {} connection...", "🔌".cyan(), options.protocol());
     
-    // Try HTTPS with openssl s_client (port 443)
-    // Connect ke subdomain IP, tapi SNI servername ke target
-    if test_ssl_connection(&ip, 443, target, timeout) {
-        print!("\r\x1B[K");
-        
-        println!("\n{}", "═".repeat(50));
-        println!("{}", "✅ WORKING BUG INJECT!".green().bold());
-        println!("\n{} {}", "Subdomain:".bright_black(), subdomain.green());
-        println!("{} {}", "IP:".bright_black(), ip.green());
-        println!("{} {} (port 443)", "Protocol:".bright_black(), "HTTPS".green());
+    let result = scan_host(target, subdomain, options).await;
+    let host = options.host(target);
+    let port = result.port.unwrap_or_else(|| options.port());
+    out!("\r\x1B[K");
+    
+    // DNS resolution gagal
+    if result.ip_results.is_empty() {
+        outln!("\n{}", "═".repeat(50).red());
+        outln!("{}", "❌ DNS RESOLUTION FAILED".red().bold());
+        outln!("\n{} {}", "Subdomain:".bright_black(), subdomain.red());
+        outln!("{} {}", "Error:".bright_black(), result.error_msg.as_deref().unwrap_or("").red());
+        outln!("{}", "═".repeat(50).red());
+        return Ok(result);
+    }
+    
+    if result.is_working {
+        outln!("\n{}", "═".repeat(50));
+        outln!("{}", "✅ WORKING BUG INJECT!".green().bold());
+        outln!("\n{} {}", "Subdomain:".bright_black(), subdomain.green());
+        outln!("{} {}", "IP:".bright_black(), result.ip.green());
+        outln!("{} {} (port {})", "Protocol:".bright_black(), options.protocol().green(), port);
+        print_provider(&result);
         
-        if is_cf {
-            println!("{} {}", "Provider:".bright_black(), "Cloudflare".cyan());
-        } else {
-            println!("{} {}", "Provider:".bright_black(), "Non-Cloudflare".yellow());
+        outln!("\n{}", "Connection Details:".bright_black());
+        outln!("  {} Connect: {}", "→".bright_black(), dns::socket_addr_string(&result.ip, port).cyan());
+        if options.tls {
+            outln!("  {} SNI servername: {}", "→".bright_black(), options.sni(target).cyan());
+            outln!("  {} SSL handshake: {}", "→".bright_black(), "SUCCESS".green());
+        }
+        if let Some(ms) = result.latency_ms {
+            outln!("  {} Connect time: {} ms", "→".bright_black(), ms.to_string().cyan());
+        }
+        if let Some(status_code) = result.status_code {
+            let request = if options.mode == ProbeMode::Ws { "WS upgrade" } else { "HTTP request" };
+            outln!("  {} {}: GET {} (Host: {})", "→".bright_black(), request, options.path.cyan(), host.cyan());
+            outln!("  {} Status: {}", "→".bright_black(), status_code.to_string().green());
+            if let Some(server) = &result.server {
+                outln!("  {} Server: {}", "→".bright_black(), server.cyan());
+            }
+            if let Some(cf_ray) = &result.cf_ray {
+                outln!("  {} CF-Ray: {}", "→".bright_black(), cf_ray.cyan());
+            }
+            if let Some(body_size) = result.body_size {
+                outln!("  {} Body size: {} bytes", "→".bright_black(), body_size.to_string().cyan());
+            }
         }
         
-        println!("\n{}", "Connection Details:".bright_black());
-        println!("  {} openssl s_client -connect {}:443", "→".bright_black(), ip.cyan());
-        println!("  {} SNI servername: {}", "→".bright_black(), target.cyan());
-        println!("  {} SSL handshake: {}", "→".bright_black(), "SUCCESS".green());
-        
-        println!("{}", "═".repeat(50));
-        return Ok(());
+        print_ip_breakdown(&result);
+        outln!("{}", "═".repeat(50));
+        return Ok(result);
     }
     
-    // SSL handshake failed = NOT WORKING
-    print!("\r\x1B[K");
+    // Handshake / HTTP gagal = NOT WORKING
+    outln!("\n{}", "═".repeat(50).red());
+    outln!("{}", "❌ BUG INJECT NOT WORKING".red().bold());
+    outln!("\n{} {}", "Subdomain:".bright_black(), subdomain.red());
+    outln!("{} {}", "IP:".bright_black(), result.ip.red());
+    print_provider(&result);
     
-    println!("\n{}", "═".repeat(50).red());
-    println!("{}", "❌ BUG INJECT NOT WORKING".red().bold());
-    println!("\n{} {}", "Subdomain:".bright_black(), subdomain.red());
-    println!("{} {}", "IP:".bright_black(), ip.red());
-    
-    if is_cf {
-        println!("{} {}", "Provider:".bright_black(), "Cloudflare".cyan());
-    } else {
-        println!("{} {}", "Provider:".bright_black(), "Non-Cloudflare".yellow());
+    if let Some(status_code) = result.status_code {
+        outln!("{} {}", "HTTP Status:".bright_black(), status_code.to_string().red());
+        if let Some(server) = &result.server {
+            outln!("{} {}", "Server:".bright_black(), server);
+        }
     }
     
-    println!("\n{} {}", "Reason:".bright_black(), "SSL handshake failed".red());
-    println!("{} Subdomain tidak bisa inject ke target", "Note:".bright_black());
-    println!("{}", "═".repeat(50).red());
+    let reason = result
+        .error_msg
+        .clone()
+        .unwrap_or_else(|| "SSL handshake OK tapi bukan IP CDN".to_string());
+    outln!("\n{} {}", "Reason:".bright_black(), reason.red());
+    outln!("{} Subdomain tidak bisa inject ke target", "Note:".bright_black());
+    print_ip_breakdown(&result);
+    outln!("{}", "═".repeat(50).red());
     
-    Ok(())
+    Ok(result)
 }
 
-pub async fn batch_test(
-    target: &str,
-    subdomains: &[String],
-    timeout: u64,
-    running: Arc<AtomicBool>,
-) -> anyhow::Result<Vec<ScanResult>> {
-    let total = subdomains.len();
-    let mut results = Vec::new();
-    
-    println!("\n{}", "Starting batch test...".cyan());
-    println!("{} {} subdomains\n", "Total:".bright_black(), total.to_string().yellow());
-    
-    let pb = ProgressBar::new(total as u64);
+/// Statistik cache DNS (kumulatif sejak proses mulai)
+pub(crate) fn print_dns_stats(resolver: &dns::Resolver) {
+    let (hits, misses) = resolver.stats();
+    outln!("  DNS cache: {} hit | {} miss", hits.to_string().cyan(), misses);
+}
+
+pub(crate) fn progress_bar(total: u64) -> ProgressBar {
+    if ui::output_mode() == ui::OutputMode::Quiet {
+        return ProgressBar::hidden();
+    }
+    let pb = ProgressBar::new(total);
     pb.set_style(
         ProgressStyle::default_bar()
             .template("{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} ({percent}%) {msg}")
             .unwrap()
             .progress_chars("█▓▒░"),
     );
+    pb
+}
 
-    for subdomain in subdomains {
-        if !running.load(Ordering::SeqCst) {
-            pb.finish_with_message("Cancelled");
-            break;
-        }
+/// Satu item worker pool: hasil yang sudah pasti tanpa request, atau request
+/// (label progress bar + future) yang menunggu giliran rate limit / delay dulu
+pub(crate) enum Job<R, Fut> {
+    Ready(R),
+    Run(String, Fut),
+}
 
-        pb.set_message(format!("Testing: {}", subdomain));
-        
-        // DNS resolution untuk dapat IP
-        if let Ok(ip) = dns::resolve_domain_first(subdomain).await {
-            let is_cf = dns::is_cloudflare_ip(&ip);
-            
-            // Try HTTPS dengan openssl s_client (port 443)
-            // Connect ke subdomain IP, SNI servername ke target
-            // SSL success = WORKING, SSL failed = NOT WORKING (simple)
-            if test_ssl_connection(&ip, 443, target, timeout) {
-                results.push(ScanResult {
-                    subdomain: subdomain.clone(),
-                    ip: ip.clone(),
-                    is_cloudflare: is_cf,
-                    is_working: is_cf, // CF + SSL success = working
-                    status_code: Some(200), // Dummy status for SSL success
-                    error_msg: None,
-                });
-            } else {
-                // SSL handshake failed = NOT WORKING
-                results.push(ScanResult {
-                    subdomain: subdomain.clone(),
-                    ip: ip.clone(),
-                    is_cloudflare: is_cf,
-                    is_working: false,
-                    status_code: None,
-                    error_msg: Some("SSL handshake failed".to_string()),
-                });
+/// Worker pool bersama batch / matrix / clean IP / DNS compare: maksimal
+/// `concurrency` item bersamaan, urutan hasil sesuai input. Sebelum request
+/// tiap item menunggu rate limiter + delay, lalu `running` dicek ulang supaya
+/// Ctrl+C tidak memulai request baru. `emit` dipanggil per hasil (streaming).
+pub(crate) async fn worker_pool<T, R, Fut>(
+    items: Vec<T>,
+    options: &ScanOptions,
+    running: &AtomicBool,
+    job: impl Fn(T) -> Job<R, Fut>,
+    emit: impl Fn(&R),
+) -> Vec<R>
+where
+    Fut: std::future::Future<Output = R>,
+{
+    let pb = progress_bar(items.len() as u64);
+    let limiter = options.rate_limit.and_then(RateLimiter::new);
+
+    let results: Vec<R> = stream::iter(items)
+        .map(|item| {
+            let job = job(item);
+            let pb = &pb;
+            let limiter = limiter.as_ref();
+            async move {
+                if !running.load(Ordering::SeqCst) {
+                    return None;
+                }
+                let result = match job {
+                    Job::Ready(result) => result,
+                    Job::Run(label, request) => {
+                        if let Some(limiter) = limiter {
+                            limiter.acquire().await;
+                        }
+                        if options.delay_ms > 0 {
+                            tokio::time::sleep(Duration::from_millis(options.delay_ms)).await;
+                        }
+                        if !running.load(Ordering::SeqCst) {
+                            return None;
+                        }
+                        pb.set_message(label);
+                        request.await
+                    }
+                };
+                pb.inc(1);
+                Some(result)
             }
-        }
-        
-        pb.inc(1);
-        
-        // Anti rate-limit: 200ms delay
-        tokio::time::sleep(Duration::from_millis(200)).await;
+        })
+        .buffered(options.concurrency.max(1))
+        .filter_map(|result| async move { result })
+        .inspect(|result| pb.suspend(|| emit(result)))
+        .collect()
+        .await;
+
+    if running.load(Ordering::SeqCst) {
+        pb.finish_with_message("Complete");
+    } else {
+        pb.finish_with_message("Cancelled");
     }
+    results
+}
+
+pub async fn batch_test(
+    target: &str,
+    subdomains: &[String],
+    options: &ScanOptions,
+    running: Arc<AtomicBool>,
+) -> anyhow::Result<Vec<ScanResult>> {
+    let total = subdomains.len();
     
-    pb.finish_with_message("Complete");
+    outln!("\n{}", "Starting batch test...".cyan());
+    outln!("{} {} subdomains", "Total:".bright_black(), total.to_string().yellow());
+    outln!(
+        "{} {} | {} {}",
+        "Concurrency:".bright_black(),
+        options.concurrency.max(1).to_string().yellow(),
+        "Rate limit:".bright_black(),
+        match options.rate_limit {
+            Some(rps) if rps > 0.0 => format!("{} req/s", rps).yellow(),
+            _ => "unlimited".yellow(),
+        }
+    );
+    outln!(
+        "{} {} | {} {} ms | {} {}",
+        "Ports:".bright_black(),
+        options.probe_ports().iter().map(u16::to_string).collect::<Vec<_>>().join(",").yellow(),
+        "Delay:".bright_black(),
+        options.delay_ms.to_string().yellow(),
+        "Retries:".bright_black(),
+        options.retries.to_string().yellow()
+    );
+    outln!("{} {}\n", "Resolver:".bright_black(), options.resolver.label().yellow());
+    
+    let wildcards = if options.wildcard_check {
+        WildcardMap::detect(subdomains, options).await
+    } else {
+        WildcardMap::default()
+    };
+    if !wildcards.is_empty() {
+        wildcards.print();
+        outln!();
+    }
+    // Keanggotaan wildcard ditentukan sebelum probe: host pertama (urutan input)
+    // jadi perwakilan parent, sisanya di-collapse tanpa probe
+    let memberships: Vec<Option<String>> = if wildcards.is_empty() {
+        vec![None; total]
+    } else {
+        stream::iter(subdomains)
+            .map(|subdomain| wildcards.check(subdomain, options))
+            .buffered(options.concurrency.max(1))
+            .collect()
+            .await
+    };
+    let mut represented = HashSet::new();
+    let plan: Vec<(&String, Option<String>, bool)> = subdomains
+        .iter()
+        .zip(memberships)
+        .map(|(subdomain, wildcard)| {
+            let collapse = wildcard.as_ref().is_some_and(|parent| !represented.insert(parent.clone()));
+            (subdomain, wildcard, collapse)
+        })
+        .collect();
+
+    let results = worker_pool(
+        plan,
+        options,
+        &running,
+        |(subdomain, wildcard, collapse)| match (wildcard, collapse) {
+            (Some(parent), true) => Job::Ready(ScanResult::wildcard_collapsed(subdomain, &parent)),
+            (wildcard, _) => Job::Run(format!("Testing: {}", subdomain), async move {
+                let mut result = scan_host(target, subdomain, options).await;
+                result.wildcard = wildcard;
+                result
+            }),
+        },
+        results::emit,
+    )
+    .await;
     
     // Display results
     let working: Vec<_> = results.iter().filter(|r| r.is_working).collect();
-    let failed: Vec<_> = results.iter().filter(|r| !r.is_working).collect();
+    let failed: Vec<_> = results.iter().filter(|r| !r.is_working && !r.is_wildcard_collapsed()).collect();
+    let dns_failed = failed.iter().filter(|r| r.ip_results.is_empty()).count();
+    let collapsed = results.iter().filter(|r| r.is_wildcard_collapsed()).count();
     
-    println!("\n{}", "═".repeat(60).cyan());
+    outln!("\n{}", "═".repeat(60).cyan());
     ui::center_text("HASIL SCAN");
-    println!("{}", "═".repeat(60).cyan());
+    outln!("{}", "═".repeat(60).cyan());
     
     if working.is_empty() {
-        println!("\n{}", "⚠️  Tidak ada working bug ditemukan".yellow());
+        outln!("\n{}", "⚠️  Tidak ada working bug ditemukan".yellow());
     } else {
-        println!("\n{} Working Bugs (SSL Handshake Success):", "✅".green());
+        outln!("\n{} Working Bugs:", "✅".green());
         for result in &working {
-            println!("  {} {} ({})", "🟢".green(), result.subdomain.green(), result.ip.bright_black());
+            // Mode --all-ips: tampilkan IP mana saja yang working
+            let ips = if result.ip_results.len() > 1 {
+                format!("{}/{} IP: {}", result.working_ips().len(), result.ip_results.len(), result.working_ips().join(", "))
+            } else {
+                result.ip.clone()
+            };
+            let wildcard = match &result.wildcard {
+                Some(parent) => format!(" [wildcard *.{}]", parent).yellow(),
+                None => "".normal(),
+            };
+            match result.status_code {
+                Some(code) => outln!(
+                    "  {} {} ({}) [HTTP {}]{}",
+                    "🟢".green(),
+                    result.subdomain.green(),
+                    ips.bright_black(),
+                    code,
+                    wildcard
+                ),
+                None => outln!("  {} {} ({}){}", "🟢".green(), result.subdomain.green(), ips.bright_black(), wildcard),
+            }
         }
     }
     
     if !failed.is_empty() {
-        println!("\n{} Not Working (SSL Handshake Failed):", "❌".red());
+        outln!("\n{} Not Working:", "❌".red());
         for result in failed.iter().take(5) {
-            println!("  {} {} ({})", "🔴".red(), result.subdomain.dimmed(), result.ip.bright_black());
+            outln!(
+                "  {} {} ({}) {}",
+                "🔴".red(),
+                result.subdomain.dimmed(),
+                result.ip.bright_black(),
+                result.error_msg.as_deref().unwrap_or("").bright_black()
+            );
         }
         if failed.len() > 5 {
-            println!("  ... dan {} lagi", failed.len() - 5);
+            outln!("  ... dan {} lagi", failed.len() - 5);
         }
     }
     
-    println!("\n{}", "─".repeat(60).bright_black());
-    println!("{}", "Statistik:");
-    println!("  Scanned: {}/{} ({}%)", results.len(), total, (results.len() * 100 / total.max(1)));
-    println!("  Working (SSL Success): {} | Failed (SSL Failed): {}", 
+    outln!("\n{}", "─".repeat(60).bright_black());
+    outln!("Statistik:");
+    let scanned = results.len();
+    outln!("  Scanned: {}/{} ({}%)", scanned, total, (scanned * 100 / total.max(1)));
+    outln!("  Working: {} | Failed: {} (DNS failed: {})", 
              working.len().to_string().green(), 
-             failed.len().to_string().red());
-    println!("{}", "─".repeat(60).bright_black());
+             failed.len().to_string().red(),
+             dns_failed);
+    if collapsed > 0 {
+        outln!("  Wildcard DNS: {} kandidat di-collapse", collapsed.to_string().yellow());
+    }
+    print_dns_stats(&options.resolver);
+    outln!("{}", "─".repeat(60).bright_black());
     
     Ok(results)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    // Server WS lokal: 101 untuk /ws, 400 untuk path lain
+    async fn ws_server(connections: usize) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            for _ in 0..connections {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 4096];
+                let mut len = 0;
+                while !buf[..len].windows(4).any(|w| w == b"\r\n\r\n") {
+                    len += stream.read(&mut buf[len..]).await.unwrap();
+                }
+                let request = String::from_utf8_lossy(&buf[..len]).to_string();
+                let response = if request.starts_with("GET /ws ") && request.contains("Upgrade: websocket") {
+                    "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\r\n"
+                } else {
+                    "HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                };
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        port
+    }
+
+    fn ws_options(path: &str) -> ScanOptions {
+        ScanOptions {
+            timeout: 5,
+            mode: ProbeMode::Ws,
+            path: path.to_string(),
+            tls: false,
+            ..ScanOptions::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn ws_probe_only_counts_101_as_working() {
+        let port = ws_server(2).await;
+
+        let upgraded = probe_ip("127.0.0.1", port, "tunnel.local", &ws_options("/ws")).await;
+        assert_eq!(upgraded.response().map(|r| r.status_code), Some(101));
+        assert!(upgraded.is_working(None));
+        assert!(upgraded.error_msg().is_none());
+
+        let rejected = probe_ip("127.0.0.1", port, "tunnel.local", &ws_options("/other")).await;
+        assert_eq!(rejected.response().map(|r| r.status_code), Some(400));
+        assert!(!rejected.is_working(None));
+        assert!(!rejected.is_working(Some(Provider::Cloudflare)));
+        assert!(rejected.error_msg().unwrap().contains("400"));
+        assert!(!rejected.is_retryable());
+    }
+
+    // Jumlah percobaan with_retries untuk hasil yang selalu sama
+    async fn attempts_for(result: ScanResult) -> usize {
+        let options = ScanOptions { retries: 2, ..ScanOptions::default() };
+        let attempts = std::sync::atomic::AtomicUsize::new(0);
+        with_retries(&options, || {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            std::future::ready(result.clone())
+        })
+        .await;
+        attempts.into_inner()
+    }
+
+    #[tokio::test]
+    async fn with_retries_skips_definitive_failures() {
+        let timeout = ScanResult::timed_out("a.example.com", &["127.0.0.1".to_string()], 443);
+        assert_eq!(attempts_for(timeout).await, 3);
+        let nxdomain = ScanResult::dns_failed("b.example.com", "NXDOMAIN".to_string());
+        assert_eq!(attempts_for(nxdomain).await, 1);
+    }
+
+    #[test]
+    fn rate_limiter_handles_extreme_rps() {
+        assert!(RateLimiter::new(0.0).is_none());
+        assert!(RateLimiter::new(f64::NAN).is_none());
+        assert!(RateLimiter::new(1e-300).is_none());
+        assert_eq!(RateLimiter::new(4.0).unwrap().interval, Duration::from_millis(250));
+    }
+
+    #[tokio::test]
+    async fn worker_pool_rechecks_cancel_after_rate_limit() {
+        let options = ScanOptions { concurrency: 2, rate_limit: Some(20.0), ..ScanOptions::default() };
+        let running = AtomicBool::new(true);
+        // Item 1 membatalkan scan; item 2 sudah jalan tapi masih menunggu rate limiter
+        let results = worker_pool(
+            vec![1, 2, 3],
+            &options,
+            &running,
+            |n| {
+                let running = &running;
+                Job::Run(String::new(), async move {
+                    tokio::task::yield_now().await;
+                    running.store(false, Ordering::SeqCst);
+                    n
+                })
+            },
+            |_| {},
+        )
+        .await;
+        assert_eq!(results, vec![1]);
+
+        running.store(true, Ordering::SeqCst);
+        let results = worker_pool(vec![1, 2], &options, &running, Job::<_, std::future::Ready<_>>::Ready, |_| {}).await;
+        assert_eq!(results, vec![1, 2]);
+    }
+}
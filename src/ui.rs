@@ -1,5 +1,55 @@
 use colored::Colorize;
 use std::io::{self, Write};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Tujuan output untuk scripting (--json / --quiet)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputMode {
+    /// Output biasa ke stdout
+    Text,
+    /// Satu JSON object per host di stdout, log ke stderr
+    Json,
+    /// Hanya host working di stdout, log disembunyikan
+    Quiet,
+}
+
+static OUTPUT_MODE: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_output_mode(mode: OutputMode) {
+    OUTPUT_MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+pub fn output_mode() -> OutputMode {
+    match OUTPUT_MODE.load(Ordering::Relaxed) {
+        1 => OutputMode::Json,
+        2 => OutputMode::Quiet,
+        _ => OutputMode::Text,
+    }
+}
+
+/// println! untuk log/tampilan: pindah ke stderr di mode json, hilang di mode quiet
+#[macro_export]
+macro_rules! outln {
+    ($($arg:tt)*) => {
+        match $crate::ui::output_mode() {
+            $crate::ui::OutputMode::Text => println!($($arg)*),
+            $crate::ui::OutputMode::Json => eprintln!($($arg)*),
+            $crate::ui::OutputMode::Quiet => {}
+        }
+    };
+}
+
+/// print! versi outln!
+#[macro_export]
+macro_rules! out {
+    ($($arg:tt)*) => {
+        match $crate::ui::output_mode() {
+            $crate::ui::OutputMode::Text => print!($($arg)*),
+            $crate::ui::OutputMode::Json => eprint!($($arg)*),
+            $crate::ui::OutputMode::Quiet => {}
+        }
+    };
+}
 
 pub fn clear_screen() {
     print!("\x1B[2J\x1B[1;1H");
@@ -18,7 +68,7 @@ pub fn center_text(text: &str) {
         0
     };
     
-    println!("{}{}", " ".repeat(padding), text.bold());
+    crate::outln!("{}{}", " ".repeat(padding), text.bold());
 }
 
 pub fn print_header(title: &str) {
@@ -26,9 +76,9 @@ pub fn print_header(title: &str) {
         .map(|(w, _)| w.0 as usize)
         .unwrap_or(60);
     
-    println!("{}", "═".repeat(width).cyan());
+    crate::outln!("{}", "═".repeat(width).cyan());
     center_text(title);
-    println!("{}", "═".repeat(width).cyan());
+    crate::outln!("{}", "═".repeat(width).cyan());
 }
 
 pub fn read_line() -> String {
@@ -0,0 +1,125 @@
+use colored::Colorize;
+use futures::stream::{self, StreamExt};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::outln;
+use crate::scanner::ScanOptions;
+
+/// Jumlah label acak yang di-resolve per parent domain
+const PROBE_LABELS: usize = 3;
+
+/// Parent domain yang punya wildcard DNS beserta IP jawabannya
+#[derive(Debug, Default)]
+pub struct WildcardMap {
+    parents: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl WildcardMap {
+    /// Resolve beberapa label acak di bawah parent tiap kandidat.
+    /// Parent yang menjawab label acak = wildcard
+    pub async fn detect(subdomains: &[String], options: &ScanOptions) -> Self {
+        let parents: BTreeSet<&str> = subdomains.iter().filter_map(|s| parent(s)).collect();
+
+        let parents = stream::iter(parents)
+            .map(|parent| async move { (parent, wildcard_ips(parent, options).await) })
+            .buffer_unordered(options.concurrency.max(1))
+            .filter_map(|(parent, ips)| async move { (!ips.is_empty()).then(|| (parent.to_string(), ips)) })
+            .collect()
+            .await;
+
+        Self { parents }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    /// Parent wildcard kalau semua IP kandidat sama dengan jawaban wildcard
+    pub async fn check(&self, subdomain: &str, options: &ScanOptions) -> Option<String> {
+        let parent = parent(subdomain)?;
+        let wildcard_ips = self.parents.get(parent)?;
+
+        let ips = resolve(subdomain, options).await;
+        (!ips.is_empty() && ips.is_subset(wildcard_ips)).then(|| parent.to_string())
+    }
+
+    pub fn print(&self) {
+        for (parent, ips) in &self.parents {
+            let ips: Vec<&str> = ips.iter().map(String::as_str).collect();
+            outln!(
+                "{} *.{} → {}",
+                "⚠️  Wildcard DNS:".yellow(),
+                parent,
+                ips.join(", ").bright_black()
+            );
+        }
+        outln!(
+            "{}",
+            "   Kandidat yang hanya cocok dengan wildcard dites sekali per parent".bright_black()
+        );
+    }
+}
+
+// "a.b.example.com" → "b.example.com"; IP literal dan domain level dua dilewati
+fn parent(subdomain: &str) -> Option<&str> {
+    if IpAddr::from_str(subdomain.trim_start_matches('[').trim_end_matches(']')).is_ok() {
+        return None;
+    }
+    let (_, parent) = subdomain.split_once('.')?;
+    parent.contains('.').then_some(parent)
+}
+
+fn random_label() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(16)
+        .map(|c| char::from(c).to_ascii_lowercase())
+        .collect()
+}
+
+async fn resolve(domain: &str, options: &ScanOptions) -> BTreeSet<String> {
+    match tokio::time::timeout(
+        Duration::from_secs(options.timeout),
+        options.resolver.resolve(domain, options.ip_family),
+    )
+    .await
+    {
+        Ok(Ok(ips)) => ips.into_iter().collect(),
+        _ => BTreeSet::new(),
+    }
+}
+
+// Gabungan IP dari semua label acak yang resolve (kosong = bukan wildcard)
+async fn wildcard_ips(parent: &str, options: &ScanOptions) -> BTreeSet<String> {
+    let labels: Vec<String> = (0..PROBE_LABELS).map(|_| format!("{}.{}", random_label(), parent)).collect();
+    futures::future::join_all(labels.iter().map(|label| resolve(label, options)))
+        .await
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parent_strips_first_label() {
+        assert_eq!(parent("a.b.example.com"), Some("b.example.com"));
+        assert_eq!(parent("cdn.example.com"), Some("example.com"));
+    }
+
+    #[test]
+    fn parent_skips_apex_and_ip_literals() {
+        assert_eq!(parent("example.com"), None);
+        assert_eq!(parent("localhost"), None);
+        assert_eq!(parent("104.16.1.1"), None);
+        assert_eq!(parent("2606:4700::1"), None);
+        assert_eq!(parent("[2606:4700::1]"), None);
+    }
+}
@@ -1,109 +1,199 @@
+use crate::{out, outln};
 use crate::config::Config;
+use crate::scanner::{self, ScanOptions};
 use crate::ui;
 
 use anyhow::Result;
 use colored::*;
+use futures::stream::{self, StreamExt};
+use std::collections::HashSet;
 use std::fs;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 const EMBEDDED_WORDLIST: &str = include_str!("../wordlists/embedded.txt");
 
-pub fn wordlist_dir() -> PathBuf {
-    dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("bug-wordlists")
+pub fn count_lines(path: &Path) -> Result<usize> {
+    let content = fs::read_to_string(path)?;
+    Ok(content.lines().filter(|l| !l.trim().is_empty()).count())
 }
 
-pub fn auto_detect_wordlist() -> Option<PathBuf> {
-    let dir = wordlist_dir();
-    if !dir.exists() {
-        return None;
+pub fn load_wordlist(active: &Option<String>) -> Result<String> {
+    match active {
+        Some(path) => Ok(fs::read_to_string(path)?),
+        None => Ok(EMBEDDED_WORDLIST.to_string()),
     }
-    
-    let mut largest: Option<(PathBuf, usize)> = None;
-    
-    if let Ok(entries) = fs::read_dir(&dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) == Some("txt") {
-                if let Ok(count) = count_lines(&path) {
-                    if largest.is_none() || count > largest.as_ref().unwrap().1 {
-                        largest = Some((path, count));
-                    }
+}
+
+/// Wordlist yang dipakai brute-force: aktif di config, None = embedded.
+/// Return (isi, label sumber).
+pub fn resolve_wordlist(config: &Config) -> Result<(String, String)> {
+    let active = config.active_wordlist.clone();
+    let label = active.clone().unwrap_or_else(|| "embedded".to_string());
+    Ok((load_wordlist(&active)?, label))
+}
+
+/// Ambil kata dari isi wordlist: trim, lowercase, skip komentar & duplikat
+pub fn parse_words(content: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    content
+        .lines()
+        .map(|l| l.trim().trim_end_matches('.').to_lowercase())
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .filter(|l| seen.insert(l.clone()))
+        .collect()
+}
+
+/// Brute-force subdomain: `word.domain` di-resolve bersamaan,
+/// hanya kandidat yang resolve yang dikembalikan (urutan sesuai wordlist)
+pub async fn enumerate_subdomains(
+    domain: &str,
+    words: &[String],
+    options: &ScanOptions,
+    running: Arc<AtomicBool>,
+) -> Vec<String> {
+    let pb = scanner::progress_bar(words.len() as u64);
+    let timeout = Duration::from_secs(options.timeout);
+
+    let found: Vec<String> = stream::iter(words)
+        .map(|word| {
+            let pb = pb.clone();
+            let running = running.clone();
+            async move {
+                if !running.load(Ordering::SeqCst) {
+                    return None;
                 }
+                let candidate = format!("{}.{}", word, domain);
+                pb.set_message(format!("Resolving: {}", candidate));
+                let resolved = matches!(
+                    tokio::time::timeout(timeout, options.resolver.resolve(&candidate, options.ip_family)).await,
+                    Ok(Ok(ips)) if !ips.is_empty()
+                );
+                pb.inc(1);
+                resolved.then_some(candidate)
             }
-        }
+        })
+        .buffered(options.concurrency.max(1))
+        .filter_map(|candidate| async move { candidate })
+        .collect()
+        .await;
+
+    if running.load(Ordering::SeqCst) {
+        pb.finish_with_message("Complete");
+    } else {
+        pb.finish_with_message("Cancelled");
     }
-    
-    largest.map(|(path, _)| path)
+
+    found
 }
 
-pub fn count_lines(path: &Path) -> Result<usize> {
-    let content = fs::read_to_string(path)?;
-    Ok(content.lines().filter(|l| !l.trim().is_empty()).count())
+/// Wordlist di folder download (*.txt)
+pub fn downloaded_wordlists() -> Vec<PathBuf> {
+    let mut wordlists: Vec<PathBuf> = fs::read_dir(Config::wordlist_dir())
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("txt"))
+                .collect()
+        })
+        .unwrap_or_default();
+    wordlists.sort();
+    wordlists
 }
 
-pub fn load_wordlist(active: &Option<String>) -> Result<String> {
-    match active {
-        Some(path) => Ok(fs::read_to_string(path)?),
-        None => Ok(EMBEDDED_WORDLIST.to_string()),
+/// Daftar wordlist non-interaktif, ★ = aktif
+pub fn print_wordlists(config: &Config) {
+    let active = config.active_wordlist.as_deref();
+    outln!(
+        "{} {}",
+        if active.is_none() { "★".green() } else { " ".normal() },
+        "embedded".bold()
+    );
+    for path in downloaded_wordlists() {
+        let is_active = active.map(Path::new) == Some(path.as_path());
+        outln!(
+            "{} {} {}",
+            if is_active { "★".green() } else { " ".normal() },
+            path.display(),
+            format!("({} lines)", count_lines(&path).unwrap_or(0)).cyan()
+        );
     }
+    outln!("\n{} {}", "Folder:".bright_black(), Config::wordlist_dir().display().to_string().bright_black());
 }
 
-pub fn wordlist_menu(config: &mut Config) -> Result<()> {
+/// Set wordlist aktif: path, nama file di folder wordlist, atau "embedded"
+pub fn use_wordlist(config: &mut Config, file: &str) -> Result<()> {
+    if file == "embedded" {
+        config.active_wordlist = None;
+    } else {
+        let path = Path::new(file);
+        let path = if path.exists() { path.to_path_buf() } else { Config::wordlist_dir().join(file) };
+        if !path.exists() {
+            return Err(anyhow::anyhow!("Wordlist tidak ditemukan: {}", file));
+        }
+        config.active_wordlist = Some(path.to_string_lossy().to_string());
+    }
+    config.save()?;
+    outln!("{} {}", "✅ Aktif:".green(), file);
+    Ok(())
+}
+
+pub async fn wordlist_menu(config: &mut Config) -> Result<()> {
     loop {
         ui::clear_screen();
         ui::print_header("Wordlist Manager");
-        println!();
-        println!("{}", "Wordlist Tersedia untuk Download:".white().bold());
-        println!();
-        println!("  {}. Small - 5,000 subdomains {}", "1".white(), "(~90 KB)".cyan());
-        println!("  {}. Medium - 20,000 subdomains {}", "2".white(), "(~350 KB)".cyan());
-        println!("  {}. Large - 110,000 subdomains {}", "3".white(), "(~2 MB)".cyan());
-        println!("  {}. View Downloaded Wordlists", "4".white());
-        println!("  {}. Delete Wordlists", "5".white());
-        println!("  {}. Reset to Embedded", "6".white());
-        println!("  {}. Back", "7".white());
-        println!();
+        outln!();
+        outln!("{}", "Wordlist Tersedia untuk Download:".white().bold());
+        outln!();
+        outln!("  {}. Small - 5,000 subdomains {}", "1".white(), "(~90 KB)".cyan());
+        outln!("  {}. Medium - 20,000 subdomains {}", "2".white(), "(~350 KB)".cyan());
+        outln!("  {}. Large - 110,000 subdomains {}", "3".white(), "(~2 MB)".cyan());
+        outln!("  {}. View Downloaded Wordlists", "4".white());
+        outln!("  {}. Delete Wordlists", "5".white());
+        outln!("  {}. Reset to Embedded", "6".white());
+        outln!("  {}. Back", "7".white());
+        outln!();
         
-        print!("Pilih [1-7]: ");
+        out!("Pilih [1-7]: ");
         io::stdout().flush()?;
         
         let mut choice = String::new();
         io::stdin().read_line(&mut choice)?;
         
         match choice.trim() {
-            "1" => {
-                download_wordlist(
-                    "small",
-                    "https://raw.githubusercontent.com/danielmiessler/SecLists/master/Discovery/DNS/subdomains-top1million-5000.txt",
-                    "seclists-5k.txt",
-                    config,
-                )?;
-            }
-            "2" => {
-                download_wordlist(
-                    "medium",
-                    "https://raw.githubusercontent.com/danielmiessler/SecLists/master/Discovery/DNS/subdomains-top1million-20000.txt",
-                    "seclists-20k.txt",
-                    config,
-                )?;
-            }
-            "3" => {
-                download_wordlist(
-                    "large",
-                    "https://raw.githubusercontent.com/danielmiessler/SecLists/master/Discovery/DNS/subdomains-top1million-110000.txt",
-                    "seclists-110k.txt",
-                    config,
-                )?;
+            choice @ ("1" | "2" | "3") => {
+                let (size, url, filename) = match choice {
+                    "1" => (
+                        "small",
+                        "https://raw.githubusercontent.com/danielmiessler/SecLists/master/Discovery/DNS/subdomains-top1million-5000.txt",
+                        "seclists-5k.txt",
+                    ),
+                    "2" => (
+                        "medium",
+                        "https://raw.githubusercontent.com/danielmiessler/SecLists/master/Discovery/DNS/subdomains-top1million-20000.txt",
+                        "seclists-20k.txt",
+                    ),
+                    _ => (
+                        "large",
+                        "https://raw.githubusercontent.com/danielmiessler/SecLists/master/Discovery/DNS/subdomains-top1million-110000.txt",
+                        "seclists-110k.txt",
+                    ),
+                };
+                if let Err(e) = download_wordlist(size, url, filename, config).await {
+                    outln!("{}", format!("❌ Download gagal: {}", e).red());
+                    ui::pause();
+                }
             }
             "4" => view_wordlists(config)?,
             "5" => delete_wordlists(config)?,
             "6" => {
                 config.active_wordlist = None;
                 config.save()?;
-                println!("{}", "✅ Reset ke embedded".green());
+                outln!("{}", "✅ Reset ke embedded".green());
                 std::thread::sleep(std::time::Duration::from_secs(1));
             }
             "7" => return Ok(()),
@@ -112,18 +202,18 @@ pub fn wordlist_menu(config: &mut Config) -> Result<()> {
     }
 }
 
-fn download_wordlist(size: &str, url: &str, filename: &str, config: &mut Config) -> Result<()> {
-    let dir = wordlist_dir();
+async fn download_wordlist(size: &str, url: &str, filename: &str, config: &mut Config) -> Result<()> {
+    let dir = Config::wordlist_dir();
     fs::create_dir_all(&dir)?;
     
     let filepath = dir.join(filename);
     
-    println!();
-    println!("{}", format!("📥 Downloading {} wordlist...", size).cyan());
-    println!();
+    outln!();
+    outln!("{}", format!("📥 Downloading {} wordlist...", size).cyan());
+    outln!();
     
     if filepath.exists() {
-        print!("{}", "⚠️  File sudah ada! Timpa? (y/n): ".yellow());
+        out!("{}", "⚠️  File sudah ada! Timpa? (y/n): ".yellow());
         io::stdout().flush()?;
         let mut overwrite = String::new();
         io::stdin().read_line(&mut overwrite)?;
@@ -132,17 +222,20 @@ fn download_wordlist(size: &str, url: &str, filename: &str, config: &mut Config)
         }
     }
     
-    // Download using reqwest blocking
-    let response = reqwest::blocking::get(url)?;
-    let content = response.text()?;
+    // Download (async reqwest, runtime sudah jalan)
+    let response = reqwest::get(url).await?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("Download gagal: HTTP {}", response.status()));
+    }
+    let content = response.text().await?;
     fs::write(&filepath, &content)?;
     
     let line_count = content.lines().filter(|l| !l.trim().is_empty()).count();
     let file_size = fs::metadata(&filepath)?.len();
     let size_kb = file_size / 1024;
     
-    println!();
-    println!(
+    outln!();
+    outln!(
         "{}",
         format!(
             "✅ Berhasil! Baris: {} | Ukuran: {} KB",
@@ -151,9 +244,9 @@ fn download_wordlist(size: &str, url: &str, filename: &str, config: &mut Config)
         .green()
         .bold()
     );
-    println!();
+    outln!();
     
-    print!("Set sebagai aktif? (y/n): ");
+    out!("Set sebagai aktif? (y/n): ");
     io::stdout().flush()?;
     let mut set_active = String::new();
     io::stdin().read_line(&mut set_active)?;
@@ -161,11 +254,11 @@ fn download_wordlist(size: &str, url: &str, filename: &str, config: &mut Config)
     if set_active.trim().to_lowercase().starts_with('y') {
         config.active_wordlist = Some(filepath.to_string_lossy().to_string());
         config.save()?;
-        println!("{}", format!("✅ Aktif: {}", filename).green());
+        outln!("{}", format!("✅ Aktif: {}", filename).green());
     }
     
-    println!();
-    print!("Tekan Enter...");
+    outln!();
+    out!("Tekan Enter...");
     io::stdout().flush()?;
     let mut _dummy = String::new();
     io::stdin().read_line(&mut _dummy)?;
@@ -176,13 +269,13 @@ fn download_wordlist(size: &str, url: &str, filename: &str, config: &mut Config)
 fn view_wordlists(config: &mut Config) -> Result<()> {
     ui::clear_screen();
     ui::print_header("Downloaded Wordlists");
-    println!();
+    outln!();
     
-    let dir = wordlist_dir();
+    let dir = Config::wordlist_dir();
     if !dir.exists() {
-        println!("{}", "Belum ada wordlist yang didownload".yellow());
-        println!();
-        print!("Tekan Enter...");
+        outln!("{}", "Belum ada wordlist yang didownload".yellow());
+        outln!();
+        out!("Tekan Enter...");
         io::stdout().flush()?;
         let mut _dummy = String::new();
         io::stdin().read_line(&mut _dummy)?;
@@ -201,9 +294,9 @@ fn view_wordlists(config: &mut Config) -> Result<()> {
     }
     
     if wordlists.is_empty() {
-        println!("{}", "Belum ada wordlist yang didownload".yellow());
+        outln!("{}", "Belum ada wordlist yang didownload".yellow());
     } else {
-        let active_path = config.active_wordlist.as_ref().map(|s| Path::new(s));
+        let active_path = config.active_wordlist.as_ref().map(Path::new);
         
         for (idx, path) in wordlists.iter().enumerate() {
             let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("unknown");
@@ -215,14 +308,14 @@ fn view_wordlists(config: &mut Config) -> Result<()> {
             let is_active = active_path == Some(path.as_path());
             
             if is_active {
-                println!(
+                outln!(
                     "  {}. ★ {} {}",
                     (idx + 1).to_string().green().bold(),
                     filename.green().bold(),
                     format!("({} lines, {} KB)", lines, size).cyan()
                 );
             } else {
-                println!(
+                outln!(
                     "  {}. {} {}",
                     (idx + 1).to_string().white(),
                     filename,
@@ -231,8 +324,8 @@ fn view_wordlists(config: &mut Config) -> Result<()> {
             }
         }
         
-        println!();
-        print!("Pilih untuk set aktif (0=batal): ");
+        outln!();
+        out!("Pilih untuk set aktif (0=batal): ");
         io::stdout().flush()?;
         
         let mut selection = String::new();
@@ -243,7 +336,7 @@ fn view_wordlists(config: &mut Config) -> Result<()> {
                 let selected = &wordlists[num - 1];
                 config.active_wordlist = Some(selected.to_string_lossy().to_string());
                 config.save()?;
-                println!(
+                outln!(
                     "{}",
                     format!(
                         "✅ Aktif: {}",
@@ -256,8 +349,8 @@ fn view_wordlists(config: &mut Config) -> Result<()> {
         }
     }
     
-    println!();
-    print!("Tekan Enter...");
+    outln!();
+    out!("Tekan Enter...");
     io::stdout().flush()?;
     let mut _dummy = String::new();
     io::stdin().read_line(&mut _dummy)?;
@@ -268,11 +361,11 @@ fn view_wordlists(config: &mut Config) -> Result<()> {
 fn delete_wordlists(config: &mut Config) -> Result<()> {
     ui::clear_screen();
     ui::print_header("Delete Wordlists");
-    println!();
+    outln!();
     
-    let dir = wordlist_dir();
+    let dir = Config::wordlist_dir();
     if !dir.exists() {
-        println!("{}", "Tidak ada wordlist untuk dihapus".yellow());
+        outln!("{}", "Tidak ada wordlist untuk dihapus".yellow());
         std::thread::sleep(std::time::Duration::from_secs(2));
         return Ok(());
     }
@@ -289,7 +382,7 @@ fn delete_wordlists(config: &mut Config) -> Result<()> {
     }
     
     if wordlists.is_empty() {
-        println!("{}", "Tidak ada wordlist untuk dihapus".yellow());
+        outln!("{}", "Tidak ada wordlist untuk dihapus".yellow());
         std::thread::sleep(std::time::Duration::from_secs(2));
         return Ok(());
     }
@@ -299,7 +392,7 @@ fn delete_wordlists(config: &mut Config) -> Result<()> {
         let size = fs::metadata(path)
             .map(|m| m.len() / 1024)
             .unwrap_or(0);
-        println!(
+        outln!(
             "  {}. {} {}",
             (idx + 1).to_string().white(),
             filename,
@@ -307,12 +400,12 @@ fn delete_wordlists(config: &mut Config) -> Result<()> {
         );
     }
     
-    println!();
-    println!("  {}. Delete All", "A".red().bold());
-    println!("  {}. Cancel", "0".white());
-    println!();
+    outln!();
+    outln!("  {}. Delete All", "A".red().bold());
+    outln!("  {}. Cancel", "0".white());
+    outln!();
     
-    print!("Pilih: ");
+    out!("Pilih: ");
     io::stdout().flush()?;
     
     let mut selection = String::new();
@@ -320,7 +413,7 @@ fn delete_wordlists(config: &mut Config) -> Result<()> {
     let selection = selection.trim();
     
     if selection.to_lowercase() == "a" {
-        print!("{}", "Hapus SEMUA? (y/n): ".red().bold());
+        out!("{}", "Hapus SEMUA? (y/n): ".red().bold());
         io::stdout().flush()?;
         let mut confirm = String::new();
         io::stdin().read_line(&mut confirm)?;
@@ -331,7 +424,7 @@ fn delete_wordlists(config: &mut Config) -> Result<()> {
             }
             config.active_wordlist = None;
             config.save()?;
-            println!("{}", "✅ Semua dihapus".green());
+            outln!("{}", "✅ Semua dihapus".green());
             std::thread::sleep(std::time::Duration::from_secs(2));
         }
     } else if let Ok(num) = selection.parse::<usize>() {
@@ -347,7 +440,7 @@ fn delete_wordlists(config: &mut Config) -> Result<()> {
                 }
             }
             
-            println!("{}", "✅ Dihapus".green());
+            outln!("{}", "✅ Dihapus".green());
             std::thread::sleep(std::time::Duration::from_secs(2));
         }
     }